@@ -1,4 +1,7 @@
 pub mod config;
 pub mod display;
+pub mod graphics;
+pub mod history;
+pub mod profile;
 pub mod valorant;
 pub mod watcher;
\ No newline at end of file
@@ -1,13 +1,17 @@
 use std::fs;
 
-use tauri::State;
+use tauri::{AppHandle, State};
+use tracing::warn;
 
 use crate::{
     configs::{
-        app_config::{AppConfig, EmbedConfigs},
+        app_config::{AppConfig, EmbedConfigs, WatcherConfig},
         app_state::AppState,
     },
-    utils::{constant_manager::CONFIG_FILE, display_manager::DisplayMode},
+    hotkey,
+    utils::{
+        constant_manager::CONFIG_FILE, display_manager::DisplayMode, hotkey_manager::HotkeyMap,
+    },
 };
 
 #[tauri::command]
@@ -16,20 +20,24 @@ pub fn load_config() -> Result<AppConfig, String> {
     Ok(app_config)
 }
 
-#[tauri::command]
-pub async fn save_config(state: State<'_, AppState>, app_config: AppConfig) -> Result<(), String> {
-    app_config.save_to_local().map_err(|e| e.to_string())?;
-
+/// 将新的监听配置同步到已创建的监听器，正在监听时重启使其生效
+pub(crate) async fn reload_watcher(
+    state: &AppState,
+    watcher_config: &WatcherConfig,
+) -> Result<(), String> {
     let mut watcher_guard = state.watcher.lock().await;
     if let Some(watcher_instance) = watcher_guard.as_mut() {
         let monitor_name = watcher_instance.display_mode.monitor_name.clone();
         watcher_instance.display_mode = DisplayMode {
-            height: app_config.watcher.height,
-            width: app_config.watcher.width,
-            refresh_rate: app_config.watcher.fps,
+            height: watcher_config.height,
+            width: watcher_config.width,
+            refresh_rate: watcher_config.fps,
             monitor_name: monitor_name,
             ..Default::default()
         };
+        watcher_instance
+            .apply_config(watcher_config)
+            .map_err(|e| e.to_string())?;
         if watcher_instance.task.lock().await.is_some() {
             watcher_instance.stop().await;
             watcher_instance.start().await;
@@ -38,6 +46,29 @@ pub async fn save_config(state: State<'_, AppState>, app_config: AppConfig) -> R
     Ok(())
 }
 
+#[tauri::command]
+pub async fn save_config(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    app_config: AppConfig,
+) -> Result<(), String> {
+    // 先校验快捷键，无效的配置不写入磁盘
+    let (_, errors) = HotkeyMap::from_config(&app_config.hotkeys);
+    if let Some(e) = errors.first() {
+        return Err(e.to_string());
+    }
+    // 重新写入由触发进程出现驱动，没有触发进程时永远不会执行
+    if app_config.watcher.reapply_tweaks_on_launch && app_config.watcher.trigger.is_none() {
+        return Err("ReapplyTweaksOnLaunch requires a Trigger".to_string());
+    }
+    app_config.save_to_local().map_err(|e| e.to_string())?;
+    // 注册失败不影响监听配置生效
+    if let Err(e) = hotkey::register(&app, &app_config.hotkeys) {
+        warn!("Failed to register hotkeys: {}", e);
+    }
+    reload_watcher(&state, &app_config.watcher).await
+}
+
 #[tauri::command]
 pub fn reset_config() -> Result<(), String> {
     let content = EmbedConfigs::get("config.toml").unwrap();
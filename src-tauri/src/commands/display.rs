@@ -1,13 +1,19 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 
 use tauri::State;
-use tracing::{debug, info};
+use tokio::sync::broadcast::Sender;
+use tracing::{debug, info, warn};
 
 use crate::{
     configs::{app_config::AppConfig, app_state::AppState},
     utils::{
-        display_manager::{enumerate_monitors, DisplayMode},
-        watcher_manager::ProcessWatcher,
+        display_manager::{
+            apply_display_mode_verified, current_display_modes, enumerate_monitors,
+            restore_display_settings, revert_display_mode, set_display_mode, DisplayMode,
+            PendingMode,
+        },
+        layout_manager::LayoutController,
+        watcher_manager::{ProcessWatcher, WatcherEvent},
     },
 };
 
@@ -39,16 +45,193 @@ pub async fn select_monitor(
         watcher_instance.display_mode.monitor_name = monitor_name;
     } else {
         let watcher_config = AppConfig::load_watcher_config().map_err(|e| e.to_string())?;
-        *watcher_guard = Some(ProcessWatcher::new(
-            watcher_config.game_path.unwrap(),
-            DisplayMode {
-                width: watcher_config.width,
-                height: watcher_config.height,
-                refresh_rate: watcher_config.fps,
-                monitor_name: monitor_name,
-                ..Default::default()
-            },
-        ));
+        let watcher = ProcessWatcher::from_config(
+            &watcher_config,
+            monitor_name,
+            state.events.clone(),
+            state.layout.clone(),
+        )
+        .map_err(|e| e.to_string())?;
+        *watcher_guard = Some(watcher);
     }
     Ok(())
 }
+
+/// 还原等待确认的模式并发布结果事件
+fn revert_pending(
+    events: &Sender<WatcherEvent>,
+    layout: &LayoutController,
+    pending: PendingMode,
+) -> Result<(), String> {
+    let result = match pending.previous.as_ref() {
+        Some(previous) => revert_display_mode(Some(previous)),
+        // 没有切换前的模式时按注册表还原，需要先还原游戏期间的布局
+        None => layout.release_then(|| revert_display_mode(None)),
+    };
+    match result {
+        Ok(()) => {
+            let _ = events.send(WatcherEvent::ModeReverted { mode: pending.mode });
+            Ok(())
+        }
+        Err(e) => {
+            let _ = events.send(WatcherEvent::ModeRevertFailed {
+                mode: pending.mode,
+                error: e.to_string(),
+            });
+            Err(e.to_string())
+        }
+    }
+}
+
+/// 临时切换并等待确认，超时未确认时自动还原到切换前的模式
+pub(crate) async fn apply_with_confirmation(
+    state: &AppState,
+    mode: DisplayMode,
+    permanent: bool,
+) -> Result<PendingMode, String> {
+    let timeout_ms = AppConfig::load_watcher_config()
+        .map_err(|e| e.to_string())?
+        .confirm_timeout_ms;
+    // 另一个显示器还在等待确认时先还原它，之后它就不会再超时还原了
+    let outstanding = {
+        let mut confirmation = state.confirmation.lock().await;
+        let other_monitor = confirmation
+            .pending()
+            .is_some_and(|p| p.mode.monitor_name != mode.monitor_name);
+        if other_monitor {
+            confirmation.take(None)
+        } else {
+            None
+        }
+    };
+    if let Some(pending) = outstanding {
+        revert_pending(&state.events, &state.layout, pending)?;
+    }
+    // 确认前只做临时切换，确认后再按需写入注册表
+    // 手动试用不是游戏会话，不发布 ModeApplied，避免任务栏和会话记录把它当成游戏
+    let previous = apply_display_mode_verified(&mode, false).map_err(|e| e.to_string())?;
+    let pending = state
+        .confirmation
+        .lock()
+        .await
+        .begin(mode, previous, permanent, timeout_ms);
+
+    let confirmation = state.confirmation.clone();
+    let events = state.events.clone();
+    let layout = state.layout.clone();
+    let id = pending.id;
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(timeout_ms)).await;
+        let expired = confirmation.lock().await.take(Some(id));
+        if let Some(pending) = expired {
+            info!("Display mode {} not confirmed, reverting", pending.mode);
+            if let Err(e) = revert_pending(&events, &layout, pending) {
+                warn!("Failed to revert display mode: {}", e);
+            }
+        }
+    });
+    Ok(pending)
+}
+
+/// 试用显示模式，需要在超时前确认，否则自动还原
+#[tauri::command]
+pub async fn try_display_mode(
+    state: State<'_, AppState>,
+    mode: DisplayMode,
+    permanent: bool,
+) -> Result<PendingMode, String> {
+    apply_with_confirmation(&state, mode, permanent).await
+}
+
+/// 保留等待确认的模式
+#[tauri::command]
+pub async fn confirm_display_mode(state: State<'_, AppState>, id: u64) -> Result<(), String> {
+    let pending = state
+        .confirmation
+        .lock()
+        .await
+        .take(Some(id))
+        .ok_or("No display mode is waiting for confirmation.")?;
+    if pending.permanent {
+        set_display_mode(&pending.mode, true).map_err(|e| e.to_string())?;
+    }
+    let _ = state
+        .events
+        .send(WatcherEvent::ModeConfirmed { mode: pending.mode });
+    Ok(())
+}
+
+/// 不等超时，立即还原等待确认的模式
+#[tauri::command]
+pub async fn cancel_display_mode(state: State<'_, AppState>) -> Result<(), String> {
+    let pending = state.confirmation.lock().await.take(None);
+    match pending {
+        Some(pending) => revert_pending(&state.events, &state.layout, pending),
+        None => Ok(()),
+    }
+}
+
+/// 立即把方案的分辨率应用到指定显示器，名称为空时使用默认显示器，需要在超时前确认
+#[tauri::command]
+pub async fn apply_profile_mode(
+    state: State<'_, AppState>,
+    profile: String,
+    monitor_name: String,
+    permanent: bool,
+) -> Result<PendingMode, String> {
+    let watcher_config = AppConfig::load_watcher_config().map_err(|e| e.to_string())?;
+    let profile = watcher_config
+        .profiles
+        .iter()
+        .find(|p| p.name == profile)
+        .ok_or(format!("Profile not found: {}", profile))?;
+    let mode = DisplayMode {
+        width: profile.width,
+        height: profile.height,
+        refresh_rate: profile.fps,
+        monitor_name,
+        ..Default::default()
+    };
+    apply_with_confirmation(&state, mode, permanent).await
+}
+
+/// 还原指定显示器的分辨率，名称为空时还原所有显示器
+#[tauri::command]
+pub async fn restore_monitor(
+    state: State<'_, AppState>,
+    monitor_name: String,
+) -> Result<(), String> {
+    {
+        // 同一显示器还在等待确认时一并取消，避免超时后再还原一次
+        let mut confirmation = state.confirmation.lock().await;
+        let same_monitor = confirmation
+            .pending()
+            .is_some_and(|p| monitor_name.is_empty() || p.mode.monitor_name == monitor_name);
+        if same_monitor {
+            confirmation.take(None);
+        }
+    }
+    match state
+        .layout
+        .release_then(|| restore_display_settings(&monitor_name))
+    {
+        Ok(()) => {
+            let _ = state.events.send(WatcherEvent::ModeRestored);
+            Ok(())
+        }
+        Err(e) => {
+            let _ = state.events.send(WatcherEvent::ModeRestoreFailed {
+                error: e.to_string(),
+            });
+            Err(e.to_string())
+        }
+    }
+}
+
+/// 每个显示器当前的分辨率和刷新率
+#[tauri::command]
+pub async fn get_current_modes() -> Result<Vec<DisplayMode>, String> {
+    tauri::async_runtime::spawn_blocking(current_display_modes)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,87 @@
+use std::fs;
+
+use crate::{
+    configs::app_config::AppConfig,
+    games::valorant::{settings_paths, tweak_sets_for, GRAPHICS_TWEAK_SET},
+    utils::{
+        graphics_manager::{
+            all_presets, read_graphics, upsert_preset, GraphicsPreset, GraphicsReadout,
+        },
+        tweak_manager::apply_tweak_sets,
+    },
+};
+
+fn find_preset(app_config: &AppConfig, name: &str) -> Result<GraphicsPreset, String> {
+    all_presets(&app_config.valorant.graphics_presets)
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or(format!("Graphics preset not found: {}", name))
+}
+
+/// 内置预设和自定义预设
+#[tauri::command]
+pub fn get_graphics_presets() -> Result<Vec<GraphicsPreset>, String> {
+    let app_config = AppConfig::load_app_config().map_err(|e| e.to_string())?;
+    Ok(all_presets(&app_config.valorant.graphics_presets))
+}
+
+/// 读取当前登录用户 GameUserSettings.ini 中的画质
+#[tauri::command]
+pub fn read_graphics_settings() -> Result<GraphicsReadout, String> {
+    let app_config = AppConfig::load_app_config().map_err(|e| e.to_string())?;
+    let settings_path = settings_paths(&app_config.valorant)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|p| p.exists())
+        .ok_or("GameUserSettings.ini not found")?;
+    let content = fs::read_to_string(settings_path).map_err(|e| e.to_string())?;
+    read_graphics(&content).map_err(|e| e.to_string())
+}
+
+/// 设为当前画质预设并写入游戏配置
+#[tauri::command]
+pub fn apply_graphics_preset(name: String) -> Result<(), String> {
+    let mut app_config = AppConfig::load_app_config().map_err(|e| e.to_string())?;
+    find_preset(&app_config, &name)?;
+    app_config.valorant.graphics_preset = Some(name);
+
+    // 与已写入的其他组一起写入，同一文件按合并后的策略加锁
+    app_config
+        .valorant
+        .applied_tweak_sets
+        .insert(GRAPHICS_TWEAK_SET.to_string());
+    let tweak_sets = tweak_sets_for(&app_config)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|s| app_config.valorant.applied_tweak_sets.contains(&s.name))
+        .collect::<Vec<_>>();
+    apply_tweak_sets(&tweak_sets).map_err(|e| e.to_string())?;
+    app_config.save_to_local().map_err(|e| e.to_string())
+}
+
+/// 保存自定义预设，同名时覆盖
+#[tauri::command]
+pub fn save_graphics_preset(preset: GraphicsPreset) -> Result<Vec<GraphicsPreset>, String> {
+    let mut app_config = AppConfig::load_app_config().map_err(|e| e.to_string())?;
+    upsert_preset(&mut app_config.valorant.graphics_presets, preset).map_err(|e| e.to_string())?;
+    app_config.save_to_local().map_err(|e| e.to_string())?;
+    Ok(all_presets(&app_config.valorant.graphics_presets))
+}
+
+/// 把预设导出为 TOML 文件
+#[tauri::command]
+pub fn export_graphics_preset(name: String, path: String) -> Result<(), String> {
+    let app_config = AppConfig::load_app_config().map_err(|e| e.to_string())?;
+    let preset = find_preset(&app_config, &name)?;
+    let content = toml::to_string_pretty(&preset).map_err(|e| e.to_string())?;
+    fs::write(path, content).map_err(|e| e.to_string())
+}
+
+/// 从 TOML 文件导入自定义预设
+#[tauri::command]
+pub fn import_graphics_preset(path: String) -> Result<GraphicsPreset, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let preset = toml::from_str::<GraphicsPreset>(&content).map_err(|e| e.to_string())?;
+    save_graphics_preset(preset.clone())?;
+    Ok(preset)
+}
@@ -0,0 +1,39 @@
+use time::UtcOffset;
+
+use crate::utils::{
+    constant_manager::HISTORY_FILE,
+    history_manager::{
+        daily_playtime, failed_switches, load_sessions, sessions_per_profile, DailyPlaytime,
+        FailedSwitches, ProfileSessions, SessionRecord,
+    },
+};
+
+fn load() -> Result<Vec<SessionRecord>, String> {
+    load_sessions(HISTORY_FILE.as_path()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_session_history(limit: Option<usize>) -> Result<Vec<SessionRecord>, String> {
+    let mut sessions = load()?;
+    sessions.reverse();
+    if let Some(limit) = limit {
+        sessions.truncate(limit);
+    }
+    Ok(sessions)
+}
+
+#[tauri::command]
+pub fn get_daily_playtime() -> Result<Vec<DailyPlaytime>, String> {
+    let offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+    Ok(daily_playtime(&load()?, offset))
+}
+
+#[tauri::command]
+pub fn get_profile_sessions() -> Result<Vec<ProfileSessions>, String> {
+    Ok(sessions_per_profile(&load()?))
+}
+
+#[tauri::command]
+pub fn get_failed_switches() -> Result<FailedSwitches, String> {
+    Ok(failed_switches(&load()?))
+}
@@ -0,0 +1,36 @@
+use tauri::State;
+
+use crate::{
+    commands::config::reload_watcher,
+    configs::{
+        app_config::{AppConfig, ProfileConfig},
+        app_state::AppState,
+    },
+    utils::watcher_manager::WatcherEvent,
+};
+
+/// 切换方案并同步到监听器
+pub(crate) async fn switch_to_profile(state: &AppState, name: &str) -> Result<(), String> {
+    let mut app_config = AppConfig::load_app_config().map_err(|e| e.to_string())?;
+    app_config
+        .watcher
+        .use_profile(name)
+        .map_err(|e| e.to_string())?;
+    app_config.save_to_local().map_err(|e| e.to_string())?;
+    reload_watcher(state, &app_config.watcher).await?;
+    let _ = state.events.send(WatcherEvent::ProfileSwitched {
+        name: name.to_string(),
+    });
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_profiles() -> Result<Vec<ProfileConfig>, String> {
+    let watcher_config = AppConfig::load_watcher_config().map_err(|e| e.to_string())?;
+    Ok(watcher_config.profiles)
+}
+
+#[tauri::command]
+pub async fn switch_profile(state: State<'_, AppState>, name: String) -> Result<(), String> {
+    switch_to_profile(&state, &name).await
+}
@@ -1,22 +1,84 @@
 pub mod init {
     use tauri::State;
 
+    use std::{
+        path::Path,
+        time::{Duration, Instant},
+    };
+
     use crate::{
-        configs::{app_config::AppConfig, app_state::AppState},
+        commands::watcher::start_watching,
+        configs::{
+            app_config::{AppConfig, ClientVariant},
+            app_state::AppState,
+        },
+        games::valorant::{
+            best_variant, diagnose_install, discover_variants, game_matcher, process_pattern,
+            InstallDiagnosis, VariantDiscovery,
+        },
         utils::{
-            command_manager::{get_running_process_path, run_command_detached},
-            display_manager::DisplayMode,
+            command_manager::get_running_process_path,
+            launch_manager::{
+                spawn_launcher, wait_for_process, LaunchError, LaunchOptions, LaunchReport,
+            },
+            matcher_manager::{MatchRule, MatcherConfig},
+            taskbar_manager::TaskbarMode,
             watcher_manager::ProcessWatcher,
         },
     };
 
+    /// 离线查找每个版本的登录器和游戏目录，按可信度排序
+    #[tauri::command]
+    pub async fn discover_installs() -> Result<Vec<VariantDiscovery>, String> {
+        tauri::async_runtime::spawn_blocking(discover_variants)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// 逐步检查当前游戏目录和配置文件，返回检查清单和修复建议
+    #[tauri::command]
+    pub fn check_install() -> Result<InstallDiagnosis, String> {
+        let app_config = AppConfig::load_app_config().map_err(|e| e.to_string())?;
+        Ok(diagnose_install(&app_config.valorant))
+    }
+
+    /// 优先读取正在运行的进程，未运行时使用离线查找的最佳结果，同时确定客户端版本
     #[tauri::command]
     pub async fn scan_game_path() -> Result<(), String> {
         let mut app_config = AppConfig::load_app_config().map_err(|e| e.to_string())?;
-        app_config.valorant.launcher_path = get_running_process_path("无畏契约登录器.exe")
-            .map(|p| p.to_string_lossy().into_owned());
-        app_config.valorant.game_path = get_running_process_path("VALORANT.exe")
-            .map(|p| p.parent().unwrap().to_string_lossy().into_owned());
+        // 正在运行的登录器可以直接确定版本
+        let running = ClientVariant::ALL.into_iter().find_map(|variant| {
+            let client = app_config.valorant.client_for(variant);
+            get_running_process_path(&client.launcher_exe).map(|p| (variant, p))
+        });
+        let mut variant = running.as_ref().map(|(variant, _)| *variant);
+        let mut launcher_path = running.map(|(_, p)| p.to_string_lossy().into_owned());
+        // 按确定的版本（未确定时按当前配置）的游戏进程名查找正在运行的游戏
+        let client = app_config
+            .valorant
+            .client_for(variant.unwrap_or(app_config.valorant.variant));
+        let mut game_path = client.process_names.iter().find_map(|name| {
+            get_running_process_path(name)
+                .and_then(|p| p.parent().map(|dir| dir.to_string_lossy().into_owned()))
+        });
+        if launcher_path.is_none() || game_path.is_none() {
+            let discoveries = discover_installs().await?;
+            let found = match variant {
+                Some(variant) => discoveries.iter().find(|d| d.variant == variant),
+                None => best_variant(&discoveries),
+            };
+            if let Some(found) = found {
+                variant = Some(found.variant);
+                launcher_path =
+                    launcher_path.or(found.report.launchers.first().map(|c| c.path.clone()));
+                game_path = game_path.or(found.report.games.first().map(|c| c.path.clone()));
+            }
+        }
+        if let Some(variant) = variant {
+            app_config.valorant.variant = variant;
+        }
+        app_config.valorant.launcher_path = launcher_path;
+        app_config.valorant.game_path = game_path;
         app_config.save_to_local().map_err(|e| e.to_string())?;
         Ok(())
     }
@@ -26,18 +88,40 @@ pub mod init {
         let mut app_config = AppConfig::load_app_config().map_err(|e| e.to_string())?;
         let watcher_config = &mut app_config.watcher;
         let valorant_config = &app_config.valorant;
+        let launcher_path = valorant_config
+            .launcher_path
+            .clone()
+            .ok_or("Launcher path not set.")?;
+
+        let client = valorant_config.client();
+        let game_exe = client
+            .process_names
+            .first()
+            .ok_or("Game process names not set.")?;
+
+        // 登录器只负责启用检测，分辨率随游戏本体进程切换和还原
+        watcher_config.game_path = Some(match &valorant_config.game_path {
+            Some(game_path) => Path::new(game_path)
+                .join(game_exe)
+                .to_string_lossy()
+                .into_owned(),
+            None => game_exe.clone(),
+        });
+        watcher_config.matcher = Some(MatcherConfig {
+            rule: MatchRule::Regex(process_pattern(&client)),
+            args: Vec::new(),
+        });
+        watcher_config.trigger = Some(MatcherConfig::path(&launcher_path));
 
         let mut watcher_guard = state.watcher.lock().await;
-        *watcher_guard = Some(ProcessWatcher::new(
-            valorant_config.launcher_path.clone().unwrap(),
-            DisplayMode {
-                width: watcher_config.width,
-                height: watcher_config.height,
-                refresh_rate: watcher_config.fps,
-                ..Default::default()
-            },
-        ));
-        watcher_config.game_path = valorant_config.launcher_path.clone();
+        let watcher = ProcessWatcher::from_config(
+            watcher_config,
+            String::new(),
+            state.events.clone(),
+            state.layout.clone(),
+        )
+        .map_err(|e| e.to_string())?;
+        *watcher_guard = Some(watcher);
         app_config.save_to_local().map_err(|e| e.to_string())?;
         Ok(())
     }
@@ -45,60 +129,198 @@ pub mod init {
     #[tauri::command]
     pub async fn start_game() -> Result<(), String> {
         let valorant_config = AppConfig::load_valrant_config().map_err(|e| e.to_string())?;
-        let launcher_path = valorant_config.launcher_path.ok_or("Launcher path not set.")?;
+        let launcher_path = valorant_config
+            .launcher_path
+            .clone()
+            .ok_or("Launcher path not set.")?;
 
-        run_command_detached(&["start", "", launcher_path.as_str()]).map_err(|e| e.to_string())?;
+        spawn_launcher(&launcher_path, &valorant_config.client().launch_args)
+            .map_err(|e| e.to_string())?;
         Ok(())
     }
 
+    /// 启动游戏并等待游戏进程出现，可先修改 cfg 文件和开始监听
     #[tauri::command]
-    pub fn hide_windows_taskbar() -> Result<(), String> {
-        Ok(())
+    pub async fn launch_game(
+        state: State<'_, AppState>,
+        options: Option<LaunchOptions>,
+    ) -> Result<LaunchReport, LaunchError> {
+        let options = options.unwrap_or_default();
+        let app_config =
+            AppConfig::load_app_config().map_err(|e| LaunchError::ConfigFailed(e.to_string()))?;
+        let client = app_config.valorant.client();
+        let launcher_path = app_config
+            .valorant
+            .launcher_path
+            .ok_or(LaunchError::LauncherNotSet)?;
+        let matcher = game_matcher(&app_config.watcher, &client)
+            .map_err(|e| LaunchError::ConfigFailed(e.to_string()))?;
+
+        if options.apply_cfg {
+            super::cfg::modify_cfg_file().map_err(LaunchError::CfgFailed)?;
+        }
+        if options.arm_watcher {
+            start_watching(&state)
+                .await
+                .map_err(LaunchError::WatcherFailed)?;
+        }
+
+        let started_at = Instant::now();
+        let args = [client.launch_args, options.args].concat();
+        spawn_launcher(&launcher_path, &args)?;
+        wait_for_process(
+            &matcher,
+            started_at,
+            Duration::from_millis(options.timeout_ms),
+        )
+        .await
+    }
+
+    /// 手动隐藏或还原任务栏，返回任务栏是否处于隐藏状态
+    #[tauri::command]
+    pub async fn hide_windows_taskbar(state: State<'_, AppState>) -> Result<bool, String> {
+        let mut taskbar = state.taskbar.lock().await;
+        if taskbar.mode() == TaskbarMode::Unchanged {
+            taskbar
+                .engage(TaskbarMode::Hide)
+                .map_err(|e| e.to_string())?;
+            Ok(true)
+        } else {
+            taskbar.release().map_err(|e| e.to_string())?;
+            Ok(false)
+        }
     }
 }
 
 pub mod cfg {
-    use std::path::Path;
-
-    use tracing::info;
+    use tokio::sync::broadcast::{error::RecvError, Sender};
+    use tracing::{info, warn};
 
     use crate::{
         configs::app_config::AppConfig,
-        games::valorant::{get_last_login_user_folder, modify_game_resolution_config},
-        utils::command_manager::run_command,
+        games::valorant::{
+            applied_tweak_sets, apply_tweaks, game_matcher, preview_tweaks, tweak_sets,
+            unlock_targets,
+        },
+        utils::{
+            matcher_manager::find_matching_processes,
+            tweak_manager::{
+                apply_to_file, enforce_protection, merge_by_target, protection_status, DriftReport,
+                ProtectionPolicy, ProtectionStatus,
+            },
+            watcher_manager::WatcherEvent,
+        },
     };
 
     #[tauri::command]
     pub fn modify_cfg_file() -> Result<(), String> {
-        let config = AppConfig::load_valrant_config().map_err(|e| e.to_string())?;
-        let user_name = get_last_login_user_folder().map_err(|e| e.to_string())?;
-        let user_settings_path = Path::new(&config.game_path.clone().unwrap()).join(format!(
-            "ShooterGame\\Saved\\Config\\{}\\WindowsClient\\GameUserSettings.ini",
-            user_name
-        ));
-        let public_settings_path = Path::new(&config.game_path.clone().unwrap())
-            .join("ShooterGame\\Saved\\Config\\WindowsClient\\GameUserSettings.ini");
-        info!("Modifying settings file: {:?}", user_settings_path);
-        modify_game_resolution_config(user_settings_path).map_err(|e| e.to_string())?;
-        info!("Modifying settings file: {:?}", public_settings_path);
-        modify_game_resolution_config(public_settings_path).map_err(|e| e.to_string())?;
-        Ok(())
+        let mut app_config = AppConfig::load_app_config().map_err(|e| e.to_string())?;
+        let applied = apply_tweaks(&app_config).map_err(|e| e.to_string())?;
+        app_config.valorant.applied_tweak_sets.extend(applied);
+        app_config.save_to_local().map_err(|e| e.to_string())
     }
 
+    /// 解除锁定，之后不再守护这些文件
     #[tauri::command]
     pub fn restore_file_pemission() -> Result<(), String> {
-        let config = AppConfig::load_valrant_config().map_err(|e| e.to_string())?;
-        let user_name = get_last_login_user_folder().map_err(|e| e.to_string())?;
-        let user_settings_path = Path::new(&config.game_path.clone().unwrap()).join(format!(
-            "ShooterGame\\Saved\\Config\\{}\\WindowsClient\\GameUserSettings.ini",
-            user_name
-        ));
-        let public_settings_path = Path::new(&config.game_path.clone().unwrap())
-            .join("ShooterGame\\Saved\\Config\\WindowsClient\\GameUserSettings.ini");
-        run_command(&["attrib", "-R", user_settings_path.to_str().unwrap()])
+        let mut app_config = AppConfig::load_app_config().map_err(|e| e.to_string())?;
+        unlock_targets(&app_config).map_err(|e| e.to_string())?;
+        app_config.valorant.applied_tweak_sets.clear();
+        app_config.save_to_local().map_err(|e| e.to_string())
+    }
+
+    /// 每个目标文件的保护策略和是否已加锁
+    #[tauri::command]
+    pub fn get_protection_status() -> Result<Vec<ProtectionStatus>, String> {
+        Ok(tweak_sets()
+            .map_err(|e| e.to_string())?
+            .iter()
+            .flat_map(protection_status)
+            .collect())
+    }
+
+    /// 保存保护策略并立即按新策略对已写入的文件加锁或解锁
+    #[tauri::command]
+    pub fn set_protection_policy(
+        tweak_set: String,
+        policy: ProtectionPolicy,
+    ) -> Result<Vec<ProtectionStatus>, String> {
+        let mut config = AppConfig::load_app_config().map_err(|e| e.to_string())?;
+        config.valorant.protection.insert(tweak_set, policy);
+        config.save_to_local().map_err(|e| e.to_string())?;
+        // 只处理用户写入过的组，未写入的组不改动文件属性
+        enforce_protection(&applied_tweak_sets().map_err(|e| e.to_string())?)
             .map_err(|e| e.to_string())?;
-        run_command(&["attrib", "-R", public_settings_path.to_str().unwrap()])
+        get_protection_status()
+    }
+
+    /// 逐键比较目标文件与当前配置，报告被游戏改回的设置
+    #[tauri::command]
+    pub fn check_cfg_drift() -> Result<Vec<DriftReport>, String> {
+        let app_config = AppConfig::load_app_config().map_err(|e| e.to_string())?;
+        preview_tweaks(&app_config).map_err(|e| e.to_string())
+    }
+
+    /// 游戏未运行时重新写入用户写入过且有偏差的目标文件，返回改动过的文件
+    fn reapply_drifted() -> Result<Vec<String>, String> {
+        let app_config = AppConfig::load_app_config().map_err(|e| e.to_string())?;
+        let matcher = game_matcher(&app_config.watcher, &app_config.valorant.client())
             .map_err(|e| e.to_string())?;
-        Ok(())
+        // 游戏运行中会持有并回写配置文件，此时写入没有意义
+        if !find_matching_processes(&matcher).is_empty() {
+            info!("Game already running, skip re-applying tweaks");
+            return Ok(Vec::new());
+        }
+        let tweak_sets = applied_tweak_sets().map_err(|e| e.to_string())?;
+        let mut files = Vec::new();
+        for target in merge_by_target(&tweak_sets)
+            .iter()
+            .filter(|t| t.path.exists())
+        {
+            if apply_to_file(&target.path, &target.tweaks, target.protection)
+                .map_err(|e| e.to_string())?
+            {
+                files.push(target.path.to_string_lossy().into_owned());
+            }
+        }
+        Ok(files)
+    }
+
+    /// 监听器检测到登录器后、游戏启动前，按配置重新写入被改回的设置
+    ///
+    /// 只响应 Armed 事件，因此需要配置触发进程；游戏已在运行时跳过。
+    pub async fn reapply_on_launch(events: Sender<WatcherEvent>) {
+        let mut receiver = events.subscribe();
+        loop {
+            match receiver.recv().await {
+                Ok(WatcherEvent::Armed { .. }) => {}
+                Ok(_) => continue,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Tweak receiver lagged, {} events skipped", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            }
+            let enabled = AppConfig::load_app_config()
+                .map(|c| c.watcher.reapply_tweaks_on_launch)
+                .unwrap_or(false);
+            if !enabled {
+                continue;
+            }
+            let result = tauri::async_runtime::spawn_blocking(reapply_drifted)
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|r| r);
+            match result {
+                Ok(files) if files.is_empty() => {}
+                Ok(files) => {
+                    let _ = events.send(WatcherEvent::TweaksReapplied { files });
+                }
+                Err(error) => {
+                    warn!("Failed to re-apply tweaks: {}", error);
+                    let _ = events.send(WatcherEvent::TweaksReapplyFailed { error });
+                }
+            }
+        }
     }
 }
@@ -1,55 +1,195 @@
-use tauri::State;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
 
 use crate::{
     configs::{app_config::AppConfig, app_state::AppState},
     utils::{
-        display_manager::{DisplayMode, change_display_mode, change_display_mode_for_monitor, restore_default_settings},
-        watcher_manager::ProcessWatcher,
+        display_manager::{apply_display_mode_verified, restore_default_settings, DisplayMode},
+        matcher_manager::{find_matching_processes, MatchedProcess, MatcherConfig, ProcessMatcher},
+        watcher_manager::{ProcessWatcher, WatcherEvent},
     },
 };
 
-#[tauri::command]
-pub async fn toggle_watching(state: State<'_, AppState>) -> Result<bool, String> {
+pub const WATCHER_EVENT: &str = "watcher-event";
+
+/// 将监听器事件转发为 Tauri 事件，前端无需轮询
+pub fn forward_events(app: AppHandle) {
+    let mut receiver = app.state::<AppState>().events.subscribe();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    info!("Watcher event: {:?}", event);
+                    if let Err(e) = app.emit(WATCHER_EVENT, &event) {
+                        warn!("Failed to emit watcher event: {}", e);
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Watcher event receiver lagged, {} events skipped", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+/// 开始监听，没有监听器实例时按配置创建
+pub(crate) async fn start_watching(state: &AppState) -> Result<(), String> {
     let mut watcher_guard = state.watcher.lock().await;
 
     if let Some(watcher_instance) = watcher_guard.as_mut() {
         if watcher_instance.task.lock().await.is_some() {
-            watcher_instance.stop().await;
-            // 关闭还原分辨率
-            restore_default_settings().map_err(|e| e.to_string())?;
-            Ok(false)
-        } else {
-            watcher_instance.start().await;
-            // 启动时当游戏正在运行，立即运行切换分辨率
-            if watcher_instance.is_running() {
-                if watcher_instance.display_mode.monitor_name.is_empty() {
-                    let _ = change_display_mode(&watcher_instance.display_mode, false);
-                } else {
-                    let _ = change_display_mode_for_monitor(&watcher_instance.display_mode, false);
-                }
-            }
-            Ok(true)
+            return Ok(());
+        }
+        watcher_instance.start().await;
+        // 启动时当游戏正在运行，立即运行切换分辨率
+        if watcher_instance.is_running() {
+            let _ = apply_display_mode(state, watcher_instance.display_mode.clone(), false);
         }
     } else {
         let watcher_config = AppConfig::load_watcher_config().map_err(|e| e.to_string())?;
-        *watcher_guard = Some(ProcessWatcher::new(
-            watcher_config.game_path.unwrap(),
+        let watcher = ProcessWatcher::from_config(
+            &watcher_config,
+            String::new(),
+            state.events.clone(),
+            state.layout.clone(),
+        )
+        .map_err(|e| e.to_string())?;
+        watcher.start().await;
+        *watcher_guard = Some(watcher);
+    }
+    Ok(())
+}
+
+/// 停止监听并还原分辨率
+pub(crate) async fn stop_watching(state: &AppState) -> Result<(), String> {
+    let watcher_guard = state.watcher.lock().await;
+
+    if let Some(watcher_instance) = watcher_guard.as_ref() {
+        if watcher_instance.task.lock().await.is_none() {
+            return Ok(());
+        }
+        // 会话中已切换的分辨率由监听器停止时还原，未切换时不发布 ModeRestored
+        watcher_instance.stop().await;
+    }
+    Ok(())
+}
+
+/// 切换分辨率并校验，发布结果事件，返回切换前的模式
+fn apply_display_mode(
+    state: &AppState,
+    mode: DisplayMode,
+    permanent: bool,
+) -> Result<Option<DisplayMode>, String> {
+    match apply_display_mode_verified(&mode, permanent) {
+        Ok(previous) => {
+            let _ = state.events.send(WatcherEvent::ModeApplied { mode });
+            Ok(previous)
+        }
+        Err(e) => {
+            let _ = state.events.send(WatcherEvent::ModeApplyFailed {
+                mode,
+                error: e.to_string(),
+            });
+            Err(e.to_string())
+        }
+    }
+}
+
+/// 还原多显示器布局和桌面分辨率并发布结果事件
+pub(crate) fn restore_display_mode(state: &AppState) -> Result<(), String> {
+    if let Err(e) = state.layout.release_then(restore_default_settings) {
+        let _ = state.events.send(WatcherEvent::ModeRestoreFailed {
+            error: e.to_string(),
+        });
+        return Err(e.to_string());
+    }
+    let _ = state.events.send(WatcherEvent::ModeRestored);
+    Ok(())
+}
+
+/// 当前方案的拉伸分辨率，优先使用监听器中的显示器设置
+pub(crate) async fn target_display_mode(state: &AppState) -> Result<DisplayMode, String> {
+    let mode = match state.watcher.lock().await.as_ref() {
+        Some(watcher_instance) => watcher_instance.display_mode.clone(),
+        None => {
+            let watcher_config = AppConfig::load_watcher_config().map_err(|e| e.to_string())?;
             DisplayMode {
                 width: watcher_config.width,
                 height: watcher_config.height,
                 refresh_rate: watcher_config.fps,
                 ..Default::default()
-            },
-        ));
-        if let Some(watcher_instance) = watcher_guard.as_mut() {
-            watcher_instance.start().await;
-            Ok(true)
-        } else {
-            Err("Failed to create watcher instance".to_string())
+            }
+        }
+    };
+    Ok(mode)
+}
+
+/// 不经过监听器，立即切换到当前方案的分辨率
+pub(crate) async fn apply_mode_now(state: &AppState) -> Result<(), String> {
+    let mode = target_display_mode(state).await?;
+    apply_display_mode(state, mode, false).map(|_| ())
+}
+
+pub(crate) async fn is_watching(state: &AppState) -> bool {
+    let watcher_guard = state.watcher.lock().await;
+    match watcher_guard.as_ref() {
+        Some(watcher_instance) => watcher_instance.task.lock().await.is_some(),
+        None => false,
+    }
+}
+
+/// 始终记录监听状态，下次启动时是否恢复由 RestoreLastState 决定
+fn remember_watching_state(watching: bool) {
+    let result = AppConfig::load_app_config().and_then(|mut app_config| {
+        if app_config.watcher.last_watching != watching {
+            app_config.watcher.last_watching = watching;
+            app_config.save_to_local()?;
+        }
+        Ok(())
+    });
+    if let Err(e) = result {
+        warn!("Failed to remember watching state: {}", e);
+    }
+}
+
+/// 程序启动时按配置自动开始监听
+pub async fn auto_start(app: AppHandle) {
+    let watcher_config = match AppConfig::load_watcher_config() {
+        Ok(watcher_config) => watcher_config,
+        Err(e) => {
+            warn!("Failed to load watcher config for auto start: {}", e);
+            return;
+        }
+    };
+    // 直接收进托盘，不在任务栏留下窗口
+    if watcher_config.start_minimized {
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.hide();
+        }
+    }
+    if watcher_config.should_watch_on_launch() {
+        info!("Auto start watching");
+        if let Err(e) = start_watching(&app.state::<AppState>()).await {
+            warn!("Failed to auto start watching: {}", e);
         }
     }
 }
 
+#[tauri::command]
+pub async fn toggle_watching(state: State<'_, AppState>) -> Result<bool, String> {
+    let watching = if is_watching(&state).await {
+        stop_watching(&state).await?;
+        false
+    } else {
+        start_watching(&state).await?;
+        true
+    };
+    remember_watching_state(watching);
+    Ok(watching)
+}
+
 #[tauri::command]
 pub async fn get_gaming_status(state: State<'_, AppState>) -> Result<bool, String> {
     let mut watcher_guard = state.watcher.lock().await;
@@ -63,16 +203,15 @@ pub async fn get_gaming_status(state: State<'_, AppState>) -> Result<bool, Strin
 
 #[tauri::command]
 pub async fn get_watching_status(state: State<'_, AppState>) -> Result<bool, String> {
-    let mut watcher_guard = state.watcher.lock().await;
+    Ok(is_watching(&state).await)
+}
 
-    if let Some(watcher_instance) = watcher_guard.as_mut() {
-        let task_guard = watcher_instance.task.lock().await;
-        if task_guard.is_some() {
-            Ok(true)
-        } else {
-            Ok(false)
-        }
-    } else {
-        Ok(false)
-    }
+/// 列出当前会被该匹配规则命中的进程，便于在保存前验证规则
+#[tauri::command]
+pub async fn test_process_matcher(matcher: MatcherConfig) -> Result<Vec<MatchedProcess>, String> {
+    let matcher = ProcessMatcher::new(matcher).map_err(|e| e.to_string())?;
+    let matched = tauri::async_runtime::spawn_blocking(move || find_matching_processes(&matcher))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(matched)
 }
@@ -1,10 +1,18 @@
 use rust_embed::RustEmbed;
 use serde::{Deserialize, Serialize};
-use std::{error::Error, fs};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fs,
+};
 use toml;
 use tracing::info;
 
-use crate::utils::constant_manager::CONFIG_FILE;
+use crate::utils::{
+    constant_manager::CONFIG_FILE, graphics_manager::GraphicsPreset, hotkey_manager::HotkeyConfig,
+    layout_manager::MonitorLayout, matcher_manager::MatcherConfig, taskbar_manager::TaskbarMode,
+    tweak_manager::ProtectionPolicy, window_manager::ApplyTrigger,
+};
 
 #[derive(RustEmbed)]
 #[folder = "configs/"]
@@ -28,6 +36,9 @@ pub struct AppConfig {
     pub valorant: ValorantConfig,
     #[serde(rename = "Development")]
     pub development: DevelopmentConfig,
+    /// 全局快捷键
+    #[serde(rename = "Hotkeys", default)]
+    pub hotkeys: Vec<HotkeyConfig>,
 }
 
 impl AppConfig {
@@ -73,14 +84,239 @@ pub struct WatcherConfig {
     pub height: u32,
     #[serde(rename = "Fps")]
     pub fps: u32,
+    /// 预设的分辨率方案，可从托盘或快捷键切换
+    #[serde(rename = "Profiles", default)]
+    pub profiles: Vec<ProfileConfig>,
+    /// 当前使用的方案名称
+    #[serde(
+        rename = "ActiveProfile",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub active_profile: Option<String>,
+    /// 进程轮询间隔（毫秒）
+    #[serde(rename = "PollIntervalMs", default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    /// 只刷新进程名/路径，检测到游戏后只轮询该 PID
+    #[serde(rename = "OptimizedPolling", default = "default_true")]
+    pub optimized_polling: bool,
+    /// 进程匹配规则，未设置时按 GamePath 精确匹配
+    #[serde(rename = "Matcher", default, skip_serializing_if = "Option::is_none")]
+    pub matcher: Option<MatcherConfig>,
+    /// 触发进程（如登录器），设置后由它启用对游戏进程的检测
+    #[serde(rename = "Trigger", default, skip_serializing_if = "Option::is_none")]
+    pub trigger: Option<MatcherConfig>,
+    /// 触发进程退出后继续检测的时间（毫秒）
+    #[serde(rename = "TriggerGraceMs", default = "default_trigger_grace_ms")]
+    pub trigger_grace_ms: u64,
+    /// 登录器重新拉起游戏的等待时间（毫秒）
+    #[serde(rename = "RestartGraceMs", default = "default_restart_grace_ms")]
+    pub restart_grace_ms: u64,
+    /// 游戏进程持续存在多久后才切换分辨率（毫秒）
+    #[serde(rename = "StartDelayMs", default)]
+    pub start_delay_ms: u64,
+    /// 游戏进程消失多久后才还原分辨率（毫秒）
+    #[serde(rename = "ExitGraceMs", default)]
+    pub exit_grace_ms: u64,
+    /// 切换后至少保持的时间（毫秒）
+    #[serde(rename = "MinSessionMs", default)]
+    pub min_session_ms: u64,
+    /// 切换时机：Process / Window / Foreground
+    #[serde(rename = "ApplyOn", default)]
+    pub apply_on: ApplyTrigger,
+    /// 切出游戏超过该时间后临时还原桌面分辨率（毫秒），0 表示不处理
+    #[serde(rename = "FocusRestoreMs", default)]
+    pub focus_restore_ms: u64,
+    /// 程序启动时自动开始监听
+    #[serde(rename = "AutoStart", default)]
+    pub auto_start: bool,
+    /// 程序启动时恢复上次退出前的监听状态
+    #[serde(rename = "RestoreLastState", default)]
+    pub restore_last_state: bool,
+    /// 上次退出前是否在监听，总会记录，只有 RestoreLastState 打开时才恢复
+    #[serde(rename = "LastWatching", default)]
+    pub last_watching: bool,
+    /// 程序启动时最小化并开始监听
+    #[serde(rename = "StartMinimized", default)]
+    pub start_minimized: bool,
+    /// 游戏期间任务栏的处理方式：Unchanged / Hide / AutoHide
+    #[serde(rename = "Taskbar", default)]
+    pub taskbar: TaskbarMode,
+    /// 检测到触发进程（登录器）后，在游戏启动前重新写入被改回的设置
+    ///
+    /// 必须同时设置 Trigger。登录器出现后才开始写入，如果游戏在下一次轮询前就已启动，
+    /// 文件已被游戏占用，本次启动不会写入，只能等下次启动登录器。
+    #[serde(rename = "ReapplyTweaksOnLaunch", default)]
+    pub reapply_tweaks_on_launch: bool,
+    /// 手动切换显示模式后等待确认的时间（毫秒），超时自动还原
+    #[serde(rename = "ConfirmTimeoutMs", default = "default_confirm_timeout_ms")]
+    pub confirm_timeout_ms: u64,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+impl WatcherConfig {
+    /// 启动时是否需要自动开始监听
+    pub fn should_watch_on_launch(&self) -> bool {
+        self.auto_start || self.start_minimized || (self.restore_last_state && self.last_watching)
+    }
+
+    /// 切换到指定方案，覆盖当前的分辨率和刷新率
+    pub fn use_profile(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
+        let profile = self
+            .profiles
+            .iter()
+            .find(|p| p.name == name)
+            .ok_or(format!("Profile not found: {}", name))?;
+        self.width = profile.width;
+        self.height = profile.height;
+        self.fps = profile.fps;
+        self.active_profile = Some(profile.name.clone());
+        Ok(())
+    }
+
+    /// 当前方案的多显示器设置
+    pub fn monitor_layout(&self) -> Option<MonitorLayout> {
+        let name = self.active_profile.as_ref()?;
+        self.profiles
+            .iter()
+            .find(|p| &p.name == name)
+            .and_then(|p| p.monitors.clone())
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ProfileConfig {
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Width")]
+    pub width: u32,
+    #[serde(rename = "Height")]
+    pub height: u32,
+    #[serde(rename = "Fps")]
+    pub fps: u32,
+    /// 游戏期间的多显示器布局，未设置时只切换分辨率
+    #[serde(rename = "Monitors", default, skip_serializing_if = "Option::is_none")]
+    pub monitors: Option<MonitorLayout>,
+}
+
+fn default_poll_interval_ms() -> u64 {
+    2000
+}
+
+fn default_trigger_grace_ms() -> u64 {
+    10_000
+}
+
+fn default_restart_grace_ms() -> u64 {
+    15_000
+}
+
+fn default_confirm_timeout_ms() -> u64 {
+    15_000
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
 pub struct ValorantConfig {
+    /// 客户端版本：国服（腾讯）或国际服（Riot Client）
+    #[serde(rename = "Variant", default)]
+    pub variant: ClientVariant,
+    /// 覆盖各版本的默认登录器、进程名和配置目录
+    #[serde(
+        rename = "Clients",
+        default,
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub clients: BTreeMap<ClientVariant, ClientProfile>,
     #[serde(rename = "LauncherPath")]
     pub launcher_path: Option<String>,
     #[serde(rename = "GamePath")]
     pub game_path: Option<String>,
+    /// 覆盖 `Saved/Config` 所在的根目录，为空时按客户端版本使用游戏目录或 `%LOCALAPPDATA%`
+    #[serde(
+        rename = "ConfigRootPath",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub config_root_path: Option<String>,
+    /// 每组键值覆盖的保护策略，未配置的使用只读
+    #[serde(rename = "Protection", default)]
+    pub protection: BTreeMap<String, ProtectionPolicy>,
+    /// 用户写入过的键值覆盖，后台只守护这些
+    #[serde(
+        rename = "AppliedTweakSets",
+        default,
+        skip_serializing_if = "BTreeSet::is_empty"
+    )]
+    pub applied_tweak_sets: BTreeSet<String>,
+    /// 当前使用的画质预设，为空时不修改画质
+    #[serde(
+        rename = "GraphicsPreset",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub graphics_preset: Option<String>,
+    /// 自定义画质预设
+    #[serde(rename = "GraphicsPresets", default)]
+    pub graphics_presets: Vec<GraphicsPreset>,
+}
+
+impl ValorantConfig {
+    /// 指定版本的客户端信息，未覆盖时使用内置默认值
+    pub fn client_for(&self, variant: ClientVariant) -> ClientProfile {
+        self.clients
+            .get(&variant)
+            .cloned()
+            .unwrap_or_else(|| variant.default_client())
+    }
+
+    /// 当前版本的客户端信息
+    pub fn client(&self) -> ClientProfile {
+        self.client_for(self.variant)
+    }
+
+    pub fn protection_for(&self, tweak_set: &str) -> ProtectionPolicy {
+        self.protection.get(tweak_set).copied().unwrap_or_default()
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ClientVariant {
+    #[default]
+    Cn,
+    Global,
+}
+
+/// 配置目录所在的根目录
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigRoot {
+    /// 游戏目录，国服的配置保存在安装目录下
+    GameDir,
+    /// `%LOCALAPPDATA%`，国际服的配置保存在用户目录下
+    LocalAppData,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ClientProfile {
+    /// 登录器的文件名
+    #[serde(rename = "LauncherExe")]
+    pub launcher_exe: String,
+    /// 启动登录器时附加的参数
+    #[serde(rename = "LaunchArgs", default)]
+    pub launch_args: Vec<String>,
+    /// 游戏本体的进程名
+    #[serde(rename = "ProcessNames")]
+    pub process_names: Vec<String>,
+    #[serde(rename = "ConfigRoot")]
+    pub config_root: ConfigRoot,
+    /// 相对于根目录的 `Saved/Config`，按路径组件列出
+    #[serde(rename = "ConfigDir")]
+    pub config_dir: Vec<String>,
+    /// `Saved\Config` 下的平台目录
+    #[serde(rename = "PlatformDir")]
+    pub platform_dir: String,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -1,20 +1,43 @@
 use std::{collections::HashMap, sync::Arc};
 
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, Mutex};
 
-use crate::utils::watcher_manager::ProcessWatcher;
+use crate::utils::{
+    constant_manager::{MONITOR_LAYOUT_FILE, TASKBAR_STATE_FILE},
+    display_manager::ModeConfirmation,
+    layout_manager::{LayoutController, Win32LayoutBackend},
+    taskbar_manager::{TaskbarController, Win32TaskbarBackend},
+    watcher_manager::{ProcessWatcher, WatcherEvent},
+};
 
 
 pub struct AppState {
     pub watcher: Arc<Mutex<Option<ProcessWatcher>>>,
     pub monitors: Arc<Mutex<HashMap<String, String>>>,
+    pub events: broadcast::Sender<WatcherEvent>,
+    pub taskbar: Arc<Mutex<TaskbarController>>,
+    /// 手动切换后等待确认的显示模式
+    pub confirmation: Arc<Mutex<ModeConfirmation>>,
+    /// 游戏期间的多显示器布局，监听任务和命令共用
+    pub layout: Arc<LayoutController>,
 }
 
 impl AppState {
     pub fn new() -> Self {
+        let (events, _) = broadcast::channel(64);
         AppState {
             watcher: Arc::new(Mutex::new(None)),
-            monitors: Arc::new(Mutex::new(HashMap::new()))
+            monitors: Arc::new(Mutex::new(HashMap::new())),
+            events,
+            taskbar: Arc::new(Mutex::new(TaskbarController::new(
+                Box::new(Win32TaskbarBackend),
+                TASKBAR_STATE_FILE.clone(),
+            ))),
+            confirmation: Arc::new(Mutex::new(ModeConfirmation::default())),
+            layout: Arc::new(LayoutController::new(
+                Box::new(Win32LayoutBackend),
+                MONITOR_LAYOUT_FILE.clone(),
+            )),
         }
     }
 }
@@ -1,139 +1,866 @@
 use std::{
-    collections::HashMap, error::Error, fs::{read_dir, read_to_string}, path::{Path, PathBuf}
+    env,
+    error::Error,
+    fs::{read_dir, read_to_string, OpenOptions},
+    path::{Path, PathBuf},
 };
 
+use serde::Serialize;
 use tracing::debug;
 
 use crate::{
-    configs::app_config::AppConfig, utils::{command_manager::run_command, text_manager::{insert_line_at, replace_multiple_parallel}}
+    configs::app_config::{
+        AppConfig, ClientProfile, ClientVariant, ConfigRoot, ValorantConfig, WatcherConfig,
+    },
+    utils::{
+        discovery_manager::{
+            available_drives, discover, Candidate, DiscoveryReport, FindingKind, GameLayout,
+            InstallProbe, InstallRootProbe, ManifestEntry, ManifestProbe, ShortcutProbe,
+            UninstallRegistryProbe,
+        },
+        file_manager::{is_read_only, set_read_only},
+        graphics_manager::all_presets,
+        matcher_manager::{MatchRule, MatcherConfig, MatcherError, ProcessMatcher},
+        tweak_manager::{
+            apply_tweak_sets, check_drift, DriftReport, ProtectionPolicy, Tweak, TweakSet,
+        },
+    },
 };
-use std::fs::write;
-
-fn get_last_login_user() -> Result<String, Box<dyn Error>> {
-    let config = AppConfig::load_valrant_config()?;
-    let user_info = read_to_string(
-        Path::new(&config.game_path.unwrap())
-            .join("ShooterGame\\Saved\\Config\\WindowsClient\\RiotLocalMachine.ini"),
-    )?;
-    debug!("user_info: \n{}", user_info);
-    let last_login_user = user_info
+
+/// 游戏本体进程：`VALORANT.exe` 或实际渲染的 `VALORANT-Win64-Shipping.exe`
+const GAME_PROCESS_NAMES: [&str; 2] = [GAME_EXE, "VALORANT-Win64-Shipping.exe"];
+
+// 游戏内的路径都写成组件列表，由 `Path::join` 按平台拼接
+const GAME_EXE: &str = "VALORANT.exe";
+const CN_CONFIG_DIR: [&str; 3] = ["ShooterGame", "Saved", "Config"];
+const GLOBAL_CONFIG_DIR: [&str; 3] = ["VALORANT", "Saved", "Config"];
+const PLATFORM_DIR: &str = "WindowsClient";
+const LOCAL_MACHINE_INI: &str = "RiotLocalMachine.ini";
+const GAME_USER_SETTINGS: &str = "GameUserSettings.ini";
+/// 安装目录到游戏根目录的子目录
+const INSTALL_SUBDIRS: [&[&str]; 2] = [&["live"], &["VALORANT", "live"]];
+const CN_LAUNCHER_DIRS: [&[&str]; 2] = [
+    &["Program Files", "ACLOS", "Launcher"],
+    &["ACLOS", "Launcher"],
+];
+const CN_GAME_DIRS: [&[&str]; 3] = [
+    &["Games", "Tencent Games", "VALORANT"],
+    &["Tencent Games", "VALORANT"],
+    &["Program Files", "Tencent Games", "VALORANT"],
+];
+const GLOBAL_LAUNCHER_DIRS: [&[&str]; 1] = [&["Riot Games", "Riot Client"]];
+const GLOBAL_GAME_DIRS: [&[&str]; 1] = [&["Riot Games", "VALORANT"]];
+const RIOT_PRODUCT_SETTINGS: [&str; 4] = [
+    "Riot Games",
+    "Metadata",
+    "valorant.live",
+    "valorant.live.product_settings.yaml",
+];
+const RIOT_CLIENT_INSTALLS: [&str; 2] = ["Riot Games", "RiotClientInstalls.json"];
+const START_MENU_PROGRAMS: [&str; 4] = ["Microsoft", "Windows", "Start Menu", "Programs"];
+
+fn components<S: AsRef<str>>(parts: &[S]) -> PathBuf {
+    parts.iter().map(|p| p.as_ref()).collect()
+}
+
+impl ClientVariant {
+    pub const ALL: [ClientVariant; 2] = [ClientVariant::Cn, ClientVariant::Global];
+
+    /// 各版本的登录器、启动参数、进程名和配置目录
+    pub fn default_client(self) -> ClientProfile {
+        let process_names = GAME_PROCESS_NAMES.iter().map(|n| n.to_string()).collect();
+        match self {
+            ClientVariant::Cn => ClientProfile {
+                launcher_exe: "无畏契约登录器.exe".to_string(),
+                launch_args: Vec::new(),
+                process_names,
+                config_root: ConfigRoot::GameDir,
+                config_dir: CN_CONFIG_DIR.map(String::from).to_vec(),
+                platform_dir: PLATFORM_DIR.to_string(),
+            },
+            ClientVariant::Global => ClientProfile {
+                launcher_exe: "RiotClientServices.exe".to_string(),
+                launch_args: vec![
+                    "--launch-product=valorant".to_string(),
+                    "--launch-patchline=live".to_string(),
+                ],
+                process_names,
+                config_root: ConfigRoot::LocalAppData,
+                config_dir: GLOBAL_CONFIG_DIR.map(String::from).to_vec(),
+                platform_dir: PLATFORM_DIR.to_string(),
+            },
+        }
+    }
+}
+
+/// 按进程名匹配游戏本体，不区分大小写
+pub fn process_pattern(client: &ClientProfile) -> String {
+    let names = client
+        .process_names
+        .iter()
+        .map(|n| regex::escape(n))
+        .collect::<Vec<_>>()
+        .join("|");
+    format!(r"(?i)(^|[\\/])({})$", names)
+}
+
+/// 优先使用监听器的匹配规则，未设置时按当前版本的进程名匹配
+pub fn game_matcher(
+    watcher_config: &WatcherConfig,
+    client: &ClientProfile,
+) -> Result<ProcessMatcher, MatcherError> {
+    let matcher_config = watcher_config.matcher.clone().unwrap_or(MatcherConfig {
+        rule: MatchRule::Regex(process_pattern(client)),
+        args: Vec::new(),
+    });
+    ProcessMatcher::new(matcher_config)
+}
+
+/// 游戏根目录下有 `VALORANT.exe` 或 `ShooterGame`，安装目录通常还要进入 `live`
+pub fn game_layout() -> GameLayout {
+    GameLayout {
+        markers: vec![GAME_EXE.to_string(), CN_CONFIG_DIR[0].to_string()],
+        subdirs: INSTALL_SUBDIRS.iter().map(|dir| components(dir)).collect(),
+    }
+}
+
+fn env_path(var: &str, relative: &[&str]) -> Option<PathBuf> {
+    env::var_os(var).map(|base| PathBuf::from(base).join(components(relative)))
+}
+
+/// 不需要游戏正在运行的安装位置探测器
+pub fn discovery_probes(variant: ClientVariant) -> Vec<Box<dyn InstallProbe>> {
+    let client = variant.default_client();
+    let (keywords, launchers, games, manifests) = match variant {
+        ClientVariant::Cn => (
+            vec!["无畏契约".to_string()],
+            &CN_LAUNCHER_DIRS[..],
+            &CN_GAME_DIRS[..],
+            Vec::new(),
+        ),
+        ClientVariant::Global => (
+            vec!["VALORANT".to_string(), "Riot Client".to_string()],
+            &GLOBAL_LAUNCHER_DIRS[..],
+            &GLOBAL_GAME_DIRS[..],
+            vec![
+                (
+                    FindingKind::Game,
+                    env_path("ProgramData", &RIOT_PRODUCT_SETTINGS),
+                    "product_install_full_path",
+                ),
+                (
+                    FindingKind::Launcher,
+                    env_path("ProgramData", &RIOT_CLIENT_INSTALLS),
+                    "rc_default",
+                ),
+            ],
+        ),
+    };
+    let shortcut_dirs = [
+        env_path("USERPROFILE", &["Desktop"]),
+        env_path("PUBLIC", &["Desktop"]),
+        env_path("APPDATA", &START_MENU_PROGRAMS),
+        env_path("ProgramData", &START_MENU_PROGRAMS),
+    ];
+    vec![
+        Box::new(InstallRootProbe {
+            roots: available_drives(),
+            launchers: launchers
+                .iter()
+                .map(|dir| components(dir).join(&client.launcher_exe))
+                .collect(),
+            games: games.iter().map(|dir| components(dir)).collect(),
+        }),
+        Box::new(ManifestProbe {
+            entries: manifests
+                .into_iter()
+                .filter_map(|(kind, file, key)| {
+                    Some(ManifestEntry {
+                        kind,
+                        file: file?,
+                        key: key.to_string(),
+                    })
+                })
+                .collect(),
+        }),
+        Box::new(ShortcutProbe {
+            dirs: shortcut_dirs.into_iter().flatten().collect(),
+            keywords: keywords.clone(),
+            launcher_names: vec![client.launcher_exe.to_lowercase()],
+        }),
+        Box::new(UninstallRegistryProbe {
+            keywords,
+            launcher_names: vec![client.launcher_exe.to_lowercase()],
+        }),
+    ]
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct VariantDiscovery {
+    pub variant: ClientVariant,
+    #[serde(flatten)]
+    pub report: DiscoveryReport,
+}
+
+/// 分别查找每个版本的安装位置
+pub fn discover_variants() -> Vec<VariantDiscovery> {
+    ClientVariant::ALL
+        .into_iter()
+        .map(|variant| VariantDiscovery {
+            variant,
+            report: discover(&discovery_probes(variant), &game_layout()),
+        })
+        .collect()
+}
+
+/// 游戏目录得分最高的版本，没有找到游戏目录时取登录器得分最高的版本
+pub fn best_variant(discoveries: &[VariantDiscovery]) -> Option<&VariantDiscovery> {
+    let top = |candidates: &[Candidate]| candidates.first().map(|c| c.score).unwrap_or(0);
+    discoveries
+        .iter()
+        .filter(|d| !d.report.games.is_empty() || !d.report.launchers.is_empty())
+        .max_by_key(|d| (top(&d.report.games), top(&d.report.launchers)))
+}
+
+/// 当前版本的 `Saved/Config` 目录，设置了 `ConfigRootPath` 时以它为根目录
+pub fn config_dir(config: &ValorantConfig) -> Result<PathBuf, Box<dyn Error>> {
+    let client = config.client();
+    let root = match (&config.config_root_path, client.config_root) {
+        (Some(root), _) => PathBuf::from(root),
+        (None, ConfigRoot::GameDir) => {
+            PathBuf::from(config.game_path.as_deref().ok_or("Game path not set")?)
+        }
+        (None, ConfigRoot::LocalAppData) => {
+            PathBuf::from(env::var_os("LOCALAPPDATA").ok_or("LOCALAPPDATA not set")?)
+        }
+    };
+    Ok(root.join(components(&client.config_dir)))
+}
+
+/// `RiotLocalMachine.ini` 中记录的最后登录用户
+fn last_known_user(content: &str) -> Option<String> {
+    content
         .lines()
-        .find(|line| line.starts_with("LastKnownUser="))
-        .ok_or("LastKnownUser not found")?
-        .replace("LastKnownUser=", "");
+        .find_map(|line| line.trim().strip_prefix("LastKnownUser="))
+        .map(|user| user.trim().to_string())
+        .filter(|user| !user.is_empty())
+}
+
+fn local_machine_ini(config_dir: &Path, platform_dir: &str) -> PathBuf {
+    config_dir.join(platform_dir).join(LOCAL_MACHINE_INI)
+}
+
+fn get_last_login_user(config_dir: &Path, platform_dir: &str) -> Result<String, Box<dyn Error>> {
+    let path = local_machine_ini(config_dir, platform_dir);
+    let user_info =
+        read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    debug!("user_info: \n{}", user_info);
+    let last_login_user = last_known_user(&user_info)
+        .ok_or(format!("LastKnownUser not found in {}", path.display()))?;
     debug!("Last login user: {}", last_login_user);
     Ok(last_login_user)
 }
 
-pub fn get_last_login_user_folder() -> Result<String, Box<dyn Error>> {
-    let user_name = get_last_login_user()?;
-    let game_path = AppConfig::load_valrant_config()?.game_path;
-    let user_name_folder =
-        read_dir(Path::new(&game_path.unwrap()).join("ShooterGame\\Saved\\Config"))?
-            .filter_map(|entry| entry.ok())
-            .find(|entry| {
-                entry
-                    .file_name()
-                    .to_str()
-                    .map(|name| name.contains(&user_name))
-                    .unwrap_or(false)
-            })
-            .ok_or("User folder not found")?
-            .file_name()
-            .into_string()
-            .map_err(|_| "Failed to convert OsString to String")?;
+/// 名称中包含用户 ID 的目录
+fn find_user_folder(config_dir: &Path, user_name: &str) -> Result<String, Box<dyn Error>> {
+    read_dir(config_dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .find_map(|entry| {
+            entry
+                .file_name()
+                .into_string()
+                .ok()
+                .filter(|name| name.contains(user_name))
+        })
+        .ok_or(
+            format!(
+                "User folder for {} not found in {}",
+                user_name,
+                config_dir.display()
+            )
+            .into(),
+        )
+}
+
+pub fn get_last_login_user_folder(
+    config_dir: &Path,
+    platform_dir: &str,
+) -> Result<String, Box<dyn Error>> {
+    let user_name = get_last_login_user(config_dir, platform_dir)?;
+    let user_name_folder = find_user_folder(config_dir, &user_name)?;
     debug!("Last login user folder: {}", user_name_folder);
     Ok(user_name_folder)
 }
 
-pub fn modify_game_resolution_config(settings_path: PathBuf) -> Result<(), Box<dyn Error>> {
-    let config = AppConfig::load_watcher_config()?;
-    let mut settings_content = read_to_string(&settings_path)?;
-    debug!("Original settings content: \n{}", settings_content);
-    let mut replacements: HashMap<usize, String> = HashMap::new();
-    for (line_number, line_content) in settings_content
-        .lines()
-        .collect::<Vec<&str>>()
-        .iter()
-        .enumerate()
-    {
-        if line_content.starts_with("bShouldLetterbox=") {
-            debug!("Found bShouldLetterbox at line {}", line_number + 1);
-            replacements.insert(line_number + 1, "bShouldLetterbox=False".to_string());
-        } else if line_content.starts_with("bLastConfirmedShouldLetterbox=") {
-            debug!(
-                "Found bLastConfirmedShouldLetterbox at line {}",
-                line_number + 1
-            );
-            replacements.insert(
-                line_number + 1,
-                "bLastConfirmedShouldLetterbox=False".to_string(),
-            );
-        } else if line_content.starts_with("bUseVSync=") {
-            debug!("Found bUseVSync at line {}", line_number + 1);
-            replacements.insert(line_number + 1, "bUseVSync=False".to_string());
-        } else if line_content.starts_with("bUseDynamicResolution=") {
-            debug!("Found bUseDynamicResolution at line {}", line_number + 1);
-            replacements.insert(line_number + 1, "bUseDynamicResolution=False".to_string());
-        } else if line_content.starts_with("ResolutionSizeX=") {
-            debug!("Found ResolutionSizeX at line {}", line_number + 1);
-            replacements.insert(line_number + 1, format!("ResolutionSizeX={}", config.width));
-        } else if line_content.starts_with("LastUserConfirmedResolutionSizeX=") {
-            debug!(
-                "Found LastUserConfirmedResolutionSizeX at line {}",
-                line_number + 1
-            );
-            replacements.insert(
-                line_number + 1,
-                format!("LastUserConfirmedResolutionSizeX={}", config.width),
-            );
-        } else if line_content.starts_with("ResolutionSizeY=") {
-            debug!("Found ResolutionSizeY at line {}", line_number + 1);
-            replacements.insert(
-                line_number + 1,
-                format!("ResolutionSizeY={}", config.height),
+/// 分辨率相关的键值覆盖名称
+pub const RESOLUTION_TWEAK_SET: &str = "Resolution";
+/// 画质预设的键值覆盖名称
+pub const GRAPHICS_TWEAK_SET: &str = "Graphics";
+
+fn settings_file(dir: &Path, client: &ClientProfile) -> PathBuf {
+    dir.join(&client.platform_dir).join(GAME_USER_SETTINGS)
+}
+
+/// 当前登录用户和公共目录下的 GameUserSettings.ini
+pub fn settings_paths(config: &ValorantConfig) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let client = config.client();
+    let config_dir = config_dir(config)?;
+    let user_name = get_last_login_user_folder(&config_dir, &client.platform_dir)?;
+    Ok(vec![
+        settings_file(&config_dir.join(user_name), &client),
+        settings_file(&config_dir, &client),
+    ])
+}
+
+/// 拉伸分辨率需要的设置：关闭黑边、垂直同步和动态分辨率，全屏模式为 2
+pub fn resolution_tweaks(width: u32, height: u32) -> Vec<Tweak> {
+    vec![
+        Tweak::new("bShouldLetterbox", "False"),
+        Tweak::new("bLastConfirmedShouldLetterbox", "False"),
+        Tweak::new("bUseVSync", "False"),
+        Tweak::new("bUseDynamicResolution", "False"),
+        Tweak::new("ResolutionSizeX", width),
+        Tweak::new("ResolutionSizeY", height),
+        Tweak::new("LastUserConfirmedResolutionSizeX", width),
+        Tweak::new("LastUserConfirmedResolutionSizeY", height),
+        Tweak::new("LastConfirmedFullscreenMode", 2),
+        Tweak::new("PreferredFullscreenMode", 2),
+        Tweak::new("FullscreenMode", 2),
+    ]
+}
+
+/// 按当前配置生成所有键值覆盖
+pub fn tweak_sets() -> Result<Vec<TweakSet>, Box<dyn Error>> {
+    tweak_sets_for(&AppConfig::load_app_config()?)
+}
+
+/// 按给定配置生成所有键值覆盖
+pub fn tweak_sets_for(config: &AppConfig) -> Result<Vec<TweakSet>, Box<dyn Error>> {
+    let targets = settings_paths(&config.valorant)?;
+    let mut tweak_sets = vec![TweakSet {
+        name: RESOLUTION_TWEAK_SET.to_string(),
+        tweaks: resolution_tweaks(config.watcher.width, config.watcher.height),
+        protection: config.valorant.protection_for(RESOLUTION_TWEAK_SET),
+        targets: targets.clone(),
+    }];
+    if let Some(name) = &config.valorant.graphics_preset {
+        let preset = all_presets(&config.valorant.graphics_presets)
+            .into_iter()
+            .find(|p| &p.name == name)
+            .ok_or(format!("Graphics preset not found: {}", name))?;
+        tweak_sets.push(TweakSet {
+            name: GRAPHICS_TWEAK_SET.to_string(),
+            tweaks: preset.settings.tweaks(),
+            protection: config.valorant.protection_for(GRAPHICS_TWEAK_SET),
+            targets,
+        });
+    }
+    Ok(tweak_sets)
+}
+
+/// 用户写入过的键值覆盖，供后台守护
+pub fn applied_tweak_sets() -> Result<Vec<TweakSet>, Box<dyn Error>> {
+    let config = AppConfig::load_app_config()?;
+    Ok(tweak_sets_for(&config)?
+        .into_iter()
+        .filter(|s| config.valorant.applied_tweak_sets.contains(&s.name))
+        .collect())
+}
+
+/// 把所有键值覆盖写入目标文件，并按合并后的保护策略加锁，返回写入的键值覆盖名称
+pub fn apply_tweaks(config: &AppConfig) -> Result<Vec<String>, Box<dyn Error>> {
+    let tweak_sets = tweak_sets_for(config)?;
+    apply_tweak_sets(&tweak_sets)?;
+    Ok(tweak_sets.into_iter().map(|s| s.name).collect())
+}
+
+/// 解除所有目标文件的只读属性，不修改内容
+pub fn unlock_targets(config: &AppConfig) -> Result<(), Box<dyn Error>> {
+    for tweak_set in tweak_sets_for(config)? {
+        for path in tweak_set.targets.iter().filter(|p| p.exists()) {
+            set_read_only(path, false)?;
+        }
+    }
+    Ok(())
+}
+
+/// 不修改文件，逐键报告目标文件与配置的差异
+pub fn preview_tweaks(config: &AppConfig) -> Result<Vec<DriftReport>, Box<dyn Error>> {
+    let mut reports = Vec::new();
+    for tweak_set in tweak_sets_for(config)? {
+        reports.extend(check_drift(&tweak_set)?);
+    }
+    Ok(reports)
+}
+
+/// 安装检查的步骤
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum InstallCheck {
+    GameExe,
+    ConfigDir,
+    LocalMachineIni,
+    UserFolder,
+    SettingsFile,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CheckStatus {
+    Passed,
+    /// 不影响使用，但可能导致部分功能失效
+    Warning,
+    Failed,
+    /// 前置步骤失败，无法检查
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CheckItem {
+    pub check: InstallCheck,
+    pub status: CheckStatus,
+    pub path: Option<String>,
+    pub detail: String,
+    /// 未通过时的修复建议
+    pub hint: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct InstallDiagnosis {
+    pub variant: ClientVariant,
+    pub items: Vec<CheckItem>,
+}
+
+impl InstallDiagnosis {
+    /// 没有失败或跳过的步骤
+    pub fn ok(&self) -> bool {
+        self.items
+            .iter()
+            .all(|item| matches!(item.status, CheckStatus::Passed | CheckStatus::Warning))
+    }
+}
+
+struct Checklist {
+    items: Vec<CheckItem>,
+}
+
+impl Checklist {
+    fn push(
+        &mut self,
+        check: InstallCheck,
+        status: CheckStatus,
+        path: Option<&Path>,
+        detail: impl Into<String>,
+        hint: Option<&str>,
+    ) -> bool {
+        self.items.push(CheckItem {
+            check,
+            status,
+            path: path.map(|p| p.to_string_lossy().into_owned()),
+            detail: detail.into(),
+            hint: hint.map(str::to_string),
+        });
+        status != CheckStatus::Failed
+    }
+
+    fn skip(&mut self, checks: &[InstallCheck]) {
+        for check in checks {
+            self.push(
+                *check,
+                CheckStatus::Skipped,
+                None,
+                "Previous check failed",
+                None,
             );
-        } else if line_content.starts_with("LastUserConfirmedResolutionSizeY=") {
-            debug!(
-                "Found LastUserConfirmedResolutionSizeY at line {}",
-                line_number + 1
+        }
+    }
+}
+
+/// 逐步检查游戏目录、配置目录、登录用户和 GameUserSettings.ini，返回检查清单
+pub fn diagnose_install(config: &ValorantConfig) -> InstallDiagnosis {
+    let mut list = Checklist { items: Vec::new() };
+    check_install(config, &mut list);
+    InstallDiagnosis {
+        variant: config.variant,
+        items: list.items,
+    }
+}
+
+fn check_install(config: &ValorantConfig, list: &mut Checklist) {
+    use InstallCheck::*;
+
+    let client = config.client();
+    let game_ok = match config.game_path.as_deref() {
+        None => list.push(
+            GameExe,
+            CheckStatus::Failed,
+            None,
+            "Game path not set",
+            Some("Scan the game path or choose the folder that contains VALORANT.exe"),
+        ),
+        Some(game_path) => {
+            let exe = Path::new(game_path).join(GAME_EXE);
+            if exe.is_file() {
+                list.push(
+                    GameExe,
+                    CheckStatus::Passed,
+                    Some(&exe),
+                    "VALORANT.exe found",
+                    None,
+                )
+            } else {
+                list.push(
+                    GameExe,
+                    CheckStatus::Failed,
+                    Some(&exe),
+                    "VALORANT.exe not found",
+                    Some("The game path should be the folder that contains VALORANT.exe, usually ending with \\live"),
+                )
+            }
+        }
+    };
+    // 国服的配置在游戏目录下，游戏目录不对时后面都无法检查
+    if !game_ok && client.config_root == ConfigRoot::GameDir && config.config_root_path.is_none() {
+        list.skip(&[ConfigDir, LocalMachineIni, UserFolder, SettingsFile]);
+        return;
+    }
+
+    let config_dir = match self::config_dir(config) {
+        Ok(dir) if dir.is_dir() => {
+            list.push(
+                ConfigDir,
+                CheckStatus::Passed,
+                Some(&dir),
+                "Config folder found",
+                None,
             );
-            replacements.insert(
-                line_number + 1,
-                format!("LastUserConfirmedResolutionSizeY={}", config.height),
+            dir
+        }
+        Ok(dir) => {
+            list.push(
+                ConfigDir,
+                CheckStatus::Failed,
+                Some(&dir),
+                "Config folder not found",
+                Some("Launch the game once so that it creates Saved\\Config, or check the client variant"),
             );
-        } else if line_content.starts_with("LastConfirmedFullscreenMode=") {
-            debug!(
-                "Found LastConfirmedFullscreenMode at line {}",
-                line_number + 1
+            list.skip(&[LocalMachineIni, UserFolder, SettingsFile]);
+            return;
+        }
+        Err(e) => {
+            list.push(ConfigDir, CheckStatus::Failed, None, e.to_string(), None);
+            list.skip(&[LocalMachineIni, UserFolder, SettingsFile]);
+            return;
+        }
+    };
+
+    let ini = local_machine_ini(&config_dir, &client.platform_dir);
+    let user = match read_to_string(&ini) {
+        Err(e) => {
+            list.push(
+                LocalMachineIni,
+                CheckStatus::Failed,
+                Some(&ini),
+                e.to_string(),
+                Some("Log in and enter the game once so that the client records the account"),
             );
-            replacements.insert(line_number + 1, "LastConfirmedFullscreenMode=2".to_string());
-        } else if line_content.starts_with("PreferredFullscreenMode=") {
-            debug!("Found PreferredFullscreenMode at line {}", line_number + 1);
-            replacements.insert(line_number + 1, "PreferredFullscreenMode=2".to_string());
-        } else if line_content.starts_with("FullscreenMode=") {
-            debug!("Found FullscreenMode at line {}", line_number + 1);
-            replacements.insert(line_number + 1, "FullscreenMode=2".to_string());
+            None
         }
+        Ok(content) => match last_known_user(&content) {
+            Some(user) => {
+                list.push(
+                    LocalMachineIni,
+                    CheckStatus::Passed,
+                    Some(&ini),
+                    format!("LastKnownUser={}", user),
+                    None,
+                );
+                Some(user)
+            }
+            None => {
+                list.push(
+                    LocalMachineIni,
+                    CheckStatus::Failed,
+                    Some(&ini),
+                    "LastKnownUser not found",
+                    Some("Log in and enter the game once so that the client records the account"),
+                );
+                None
+            }
+        },
+    };
+
+    let user_folder = match user {
+        None => {
+            list.skip(&[UserFolder]);
+            None
+        }
+        Some(user) => match find_user_folder(&config_dir, &user) {
+            Ok(folder) => {
+                let path = config_dir.join(&folder);
+                list.push(UserFolder, CheckStatus::Passed, Some(&path), folder, None);
+                Some(path)
+            }
+            Err(e) => {
+                list.push(
+                    UserFolder,
+                    CheckStatus::Failed,
+                    Some(&config_dir),
+                    e.to_string(),
+                    Some("Enter the game once with this account so that its settings folder is created"),
+                );
+                None
+            }
+        },
+    };
+
+    // 用户目录找不到时仍然检查公共目录下的设置文件
+    let mut settings = Vec::new();
+    if let Some(folder) = &user_folder {
+        settings.push((settings_file(folder, &client), true));
+    }
+    settings.push((settings_file(&config_dir, &client), false));
+    let expect_locked = [RESOLUTION_TWEAK_SET, GRAPHICS_TWEAK_SET]
+        .iter()
+        .any(|name| config.protection_for(name) == ProtectionPolicy::ReadOnly);
+    for (path, required) in settings {
+        check_settings_file(list, &path, required, expect_locked);
+    }
+}
+
+fn check_settings_file(list: &mut Checklist, path: &Path, required: bool, expect_locked: bool) {
+    use InstallCheck::SettingsFile;
+
+    if !path.is_file() {
+        let status = if required {
+            CheckStatus::Failed
+        } else {
+            CheckStatus::Warning
+        };
+        list.push(
+            SettingsFile,
+            status,
+            Some(path),
+            "GameUserSettings.ini not found",
+            Some("Change any video setting in game so that the file is written"),
+        );
+        return;
+    }
+    match is_read_only(path) {
+        Ok(true) if expect_locked => list.push(
+            SettingsFile,
+            CheckStatus::Passed,
+            Some(path),
+            "Read-only, protected by the current policy",
+            None,
+        ),
+        Ok(true) => list.push(
+            SettingsFile,
+            CheckStatus::Warning,
+            Some(path),
+            "Read-only, the game cannot save settings",
+            Some("Restore file permission or set the protection policy to ReadOnly"),
+        ),
+        Ok(false) => match OpenOptions::new().append(true).open(path) {
+            Ok(_) => list.push(
+                SettingsFile,
+                CheckStatus::Passed,
+                Some(path),
+                "Writable",
+                None,
+            ),
+            Err(e) => list.push(
+                SettingsFile,
+                CheckStatus::Failed,
+                Some(path),
+                format!("Not writable: {}", e),
+                Some("Close the game and check that the folder is not under a protected location"),
+            ),
+        },
+        Err(e) => list.push(
+            SettingsFile,
+            CheckStatus::Failed,
+            Some(path),
+            e.to_string(),
+            None,
+        ),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_util::TempDir;
+
+    fn discovery(variant: ClientVariant, games: &[u32], launchers: &[u32]) -> VariantDiscovery {
+        let candidates = |scores: &[u32]| {
+            scores
+                .iter()
+                .map(|score| Candidate {
+                    path: format!("{:?}-{}", variant, score),
+                    score: *score,
+                    evidence: Vec::new(),
+                })
+                .collect()
+        };
+        VariantDiscovery {
+            variant,
+            report: DiscoveryReport {
+                launchers: candidates(launchers),
+                games: candidates(games),
+            },
+        }
+    }
+
+    #[test]
+    fn process_pattern_matches_both_game_processes() {
+        for variant in ClientVariant::ALL {
+            let pattern = regex::Regex::new(&process_pattern(&variant.default_client())).unwrap();
+            assert!(pattern.is_match(r"E:\Games\VALORANT\live\valorant.exe"));
+            assert!(pattern.is_match(
+                r"C:\Riot Games\VALORANT\live\ShooterGame\Binaries\Win64\VALORANT-Win64-Shipping.exe"
+            ));
+            assert!(!pattern.is_match(r"E:\x\NOTVALORANT.exe"));
+        }
+    }
+
+    #[test]
+    fn best_variant_prefers_game_score_then_launcher_score() {
+        let discoveries = [
+            discovery(ClientVariant::Cn, &[40], &[90]),
+            discovery(ClientVariant::Global, &[60], &[]),
+        ];
+        assert_eq!(
+            best_variant(&discoveries).unwrap().variant,
+            ClientVariant::Global
+        );
+
+        let discoveries = [
+            discovery(ClientVariant::Cn, &[], &[30]),
+            discovery(ClientVariant::Global, &[], &[]),
+        ];
+        assert_eq!(
+            best_variant(&discoveries).unwrap().variant,
+            ClientVariant::Cn
+        );
+
+        let discoveries = [discovery(ClientVariant::Global, &[], &[])];
+        assert!(best_variant(&discoveries).is_none());
+    }
+
+    fn statuses(diagnosis: &InstallDiagnosis) -> Vec<(InstallCheck, CheckStatus)> {
+        diagnosis
+            .items
+            .iter()
+            .map(|item| (item.check, item.status))
+            .collect()
+    }
+
+    #[test]
+    fn last_known_user_ignores_blank_values() {
+        assert_eq!(
+            last_known_user("[Riot]\r\nLastKnownUser=abc-123\r\n").as_deref(),
+            Some("abc-123")
+        );
+        assert_eq!(last_known_user("LastKnownUser=\n"), None);
+        assert_eq!(last_known_user("[Riot]\n"), None);
+    }
+
+    #[test]
+    fn diagnose_install_walks_the_checklist() {
+        use CheckStatus::*;
+        use InstallCheck::*;
+
+        let root = TempDir::new("diagnose");
+        let client = ClientVariant::Cn.default_client();
+        let mut config = ValorantConfig {
+            game_path: Some(root.path().to_string_lossy().into_owned()),
+            ..Default::default()
+        };
+
+        // 游戏目录不对时跳过后续步骤
+        let diagnosis = diagnose_install(&config);
+        assert_eq!(
+            statuses(&diagnosis),
+            [
+                (GameExe, Failed),
+                (ConfigDir, Skipped),
+                (LocalMachineIni, Skipped),
+                (UserFolder, Skipped),
+                (SettingsFile, Skipped),
+            ]
+        );
+        assert!(diagnosis.items[0].hint.is_some());
+
+        std::fs::write(root.join("VALORANT.exe"), "").unwrap();
+        let config_dir = root.join(components(&client.config_dir));
+        let platform = config_dir.join(&client.platform_dir);
+        std::fs::create_dir_all(&platform).unwrap();
+        std::fs::write(
+            platform.join("RiotLocalMachine.ini"),
+            "[Riot]\nLastKnownUser=abc\n",
+        )
+        .unwrap();
+        let diagnosis = diagnose_install(&config);
+        assert_eq!(
+            statuses(&diagnosis),
+            [
+                (GameExe, Passed),
+                (ConfigDir, Passed),
+                (LocalMachineIni, Passed),
+                (UserFolder, Failed),
+                (SettingsFile, Warning),
+            ]
+        );
+        assert!(!diagnosis.ok());
+
+        let user_platform = config_dir.join("abc-ap").join(&client.platform_dir);
+        std::fs::create_dir_all(&user_platform).unwrap();
+        std::fs::write(user_platform.join("GameUserSettings.ini"), "").unwrap();
+        std::fs::write(platform.join("GameUserSettings.ini"), "").unwrap();
+        let diagnosis = diagnose_install(&config);
+        assert!(diagnosis.ok());
+        assert_eq!(diagnosis.items.len(), 6);
+
+        // 未启用只读保护时，只读文件只给出警告
+        set_read_only(&platform.join("GameUserSettings.ini"), true).unwrap();
+        config
+            .protection
+            .insert(RESOLUTION_TWEAK_SET.to_string(), ProtectionPolicy::None);
+        config
+            .protection
+            .insert(GRAPHICS_TWEAK_SET.to_string(), ProtectionPolicy::None);
+        let diagnosis = diagnose_install(&config);
+        assert_eq!(diagnosis.items[5].status, Warning);
+        assert!(diagnosis.ok());
+
+        set_read_only(&platform.join("GameUserSettings.ini"), false).unwrap();
+    }
+
+    #[test]
+    fn config_root_override_is_checked_without_game_path() {
+        use CheckStatus::*;
+        use InstallCheck::*;
+
+        let root = TempDir::new("config_root");
+        let client = ClientVariant::Cn.default_client();
+        std::fs::create_dir_all(root.join(components(&client.config_dir))).unwrap();
+        let config = ValorantConfig {
+            config_root_path: Some(root.path().to_string_lossy().into_owned()),
+            ..Default::default()
+        };
+
+        // 国服覆盖了配置根目录时，游戏目录不对也继续检查配置
+        let diagnosis = diagnose_install(&config);
+        assert_eq!(
+            statuses(&diagnosis)[..3],
+            [
+                (GameExe, Failed),
+                (ConfigDir, Passed),
+                (LocalMachineIni, Failed)
+            ]
+        );
     }
-    settings_content = replace_multiple_parallel(settings_content, &replacements)?;
-    debug!("Replacements collected: {:?}", replacements);
-    if settings_content
-        .lines()
-        .all(|line| !line.starts_with("FullscreenMode="))
-    {
-        debug!("FullscreenMode not found, inserting it at the start");
-        settings_content = insert_line_at(settings_content, 1, 1, "FullscreenMode=2".to_string())?;
-    }
-    let target_file = settings_path.to_str().unwrap();
-    debug!("Unlocking file: {}", target_file);
-    run_command(&["attrib", "-R", target_file])?;
-    debug!(
-        "Writing modified settings content to file: {:?}",
-        settings_path
-    );
-    write(settings_path.clone(), settings_content)?;
-    debug!("Locking file: {}", target_file);
-    run_command(&["attrib", "+R", target_file])?;
-    Ok(())
 }
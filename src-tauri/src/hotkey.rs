@@ -0,0 +1,183 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use tracing::{info, warn};
+
+use crate::{
+    commands::{
+        profile::switch_to_profile,
+        watcher::{apply_mode_now, restore_display_mode, target_display_mode, toggle_watching},
+    },
+    configs::{app_config::AppConfig, app_state::AppState},
+    utils::{
+        display_manager::get_display_mode_for_monitor,
+        hotkey_manager::{
+            next_profile, toggle_command, Accelerator, HotkeyAction, HotkeyConfig, HotkeyMap,
+            StretchCommand,
+        },
+    },
+};
+
+/// 已注册快捷键的 id 到动作的映射，按下时插件回传同一组合键的 id
+#[derive(Default)]
+struct HotkeyRegistry(Mutex<HashMap<u32, HotkeyAction>>);
+
+impl HotkeyRegistry {
+    fn bind(&self, shortcut: &Shortcut, action: HotkeyAction) {
+        self.0.lock().unwrap().insert(shortcut.id(), action);
+    }
+
+    fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+
+    fn action_for(&self, shortcut: &Shortcut) -> Option<HotkeyAction> {
+        self.0.lock().unwrap().get(&shortcut.id()).copied()
+    }
+}
+
+/// 把组合键转换为插件的快捷键，插件不支持的主键跳过
+fn shortcuts(map: &HotkeyMap) -> Vec<(&Accelerator, Shortcut, HotkeyAction)> {
+    map.bindings()
+        .filter_map(
+            |(accelerator, action)| match accelerator.to_string().parse::<Shortcut>() {
+                Ok(shortcut) => Some((accelerator, shortcut, action)),
+                Err(e) => {
+                    warn!("Unsupported hotkey {}: {}", accelerator, e);
+                    None
+                }
+            },
+        )
+        .collect()
+}
+
+async fn run_action(app: &AppHandle, action: HotkeyAction) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    match action {
+        HotkeyAction::ToggleStretched => {
+            let target = target_display_mode(&state).await?;
+            // 与目标显示器比较，方案指定了其他显示器时不能读默认显示器
+            let current =
+                get_display_mode_for_monitor(&target.monitor_name).map_err(|e| e.to_string())?;
+            match toggle_command(
+                (current.width, current.height),
+                (target.width, target.height),
+            ) {
+                StretchCommand::Apply => apply_mode_now(&state).await,
+                StretchCommand::Restore => restore_display_mode(&state),
+            }
+        }
+        HotkeyAction::CycleProfile => {
+            let watcher_config = AppConfig::load_watcher_config().map_err(|e| e.to_string())?;
+            let Some(profile) = next_profile(
+                &watcher_config.profiles,
+                watcher_config.active_profile.as_deref(),
+            ) else {
+                return Ok(());
+            };
+            switch_to_profile(&state, &profile.name).await
+        }
+        HotkeyAction::PauseWatcher => toggle_watching(state).await.map(|_| ()),
+    }
+}
+
+/// 按配置重新注册全局快捷键，单个快捷键无效或被占用时跳过
+pub fn register(app: &AppHandle, hotkeys: &[HotkeyConfig]) -> Result<(), String> {
+    let (map, errors) = HotkeyMap::from_config(hotkeys);
+    for e in errors {
+        warn!("Skipped hotkey: {}", e);
+    }
+    let global_shortcut = app.global_shortcut();
+    global_shortcut
+        .unregister_all()
+        .map_err(|e| e.to_string())?;
+
+    let registry = app.state::<HotkeyRegistry>();
+    registry.clear();
+    for (accelerator, shortcut, action) in shortcuts(&map) {
+        match global_shortcut.register(shortcut) {
+            Ok(()) => {
+                info!("Registered hotkey {} for {:?}", accelerator, action);
+                registry.bind(&shortcut, action);
+            }
+            Err(e) => warn!("Failed to register hotkey {}: {}", accelerator, e),
+        }
+    }
+    Ok(())
+}
+
+/// 安装全局快捷键插件并注册配置中的快捷键
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    app.manage(HotkeyRegistry::default());
+    app.plugin(
+        tauri_plugin_global_shortcut::Builder::new()
+            .with_handler(|app, shortcut, event| {
+                if event.state() != ShortcutState::Pressed {
+                    return;
+                }
+                let Some(action) = app.state::<HotkeyRegistry>().action_for(shortcut) else {
+                    return;
+                };
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = run_action(&app, action).await {
+                        warn!("Hotkey action {:?} failed: {}", action, e);
+                    }
+                });
+            })
+            .build(),
+    )?;
+
+    let hotkeys = AppConfig::load_app_config()
+        .map(|c| c.hotkeys)
+        .unwrap_or_default();
+    if let Err(e) = register(app, &hotkeys) {
+        warn!("Failed to register hotkeys: {}", e);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tauri_plugin_global_shortcut::{Code, Modifiers};
+
+    use super::*;
+
+    fn hotkey(action: HotkeyAction, keys: &str) -> HotkeyConfig {
+        HotkeyConfig {
+            action,
+            keys: keys.to_string(),
+        }
+    }
+
+    #[test]
+    fn pressed_shortcut_finds_configured_action() {
+        let (map, _) = HotkeyMap::from_config(&[
+            hotkey(HotkeyAction::ToggleStretched, "alt+ctrl+f9"),
+            hotkey(HotkeyAction::PauseWatcher, "Shift+Win+P"),
+        ]);
+        let registry = HotkeyRegistry::default();
+        for (_, shortcut, action) in shortcuts(&map) {
+            registry.bind(&shortcut, action);
+        }
+
+        // 插件回传的是按下的组合键，与配置的书写顺序和大小写无关
+        let pressed = Shortcut::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::F9);
+        assert_eq!(
+            registry.action_for(&pressed),
+            Some(HotkeyAction::ToggleStretched)
+        );
+        let pressed = Shortcut::new(Some(Modifiers::SHIFT | Modifiers::SUPER), Code::KeyP);
+        assert_eq!(
+            registry.action_for(&pressed),
+            Some(HotkeyAction::PauseWatcher)
+        );
+        let other = Shortcut::new(Some(Modifiers::CONTROL), Code::F9);
+        assert_eq!(registry.action_for(&other), None);
+
+        registry.clear();
+        let pressed = Shortcut::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::F9);
+        assert_eq!(registry.action_for(&pressed), None);
+    }
+}
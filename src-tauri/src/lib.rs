@@ -1,10 +1,22 @@
-use crate::configs::app_state::AppState;
+use tauri::{Manager, WindowEvent};
+use tracing::warn;
+
+use crate::{
+    configs::app_state::AppState,
+    games::valorant::applied_tweak_sets,
+    utils::{
+        history_manager::record_sessions, taskbar_manager::follow_sessions,
+        tweak_manager::guard_tweak_sets,
+    },
+};
 
 pub mod configs;
 pub mod games;
 pub mod utils;
 
 mod commands;
+mod hotkey;
+mod tray;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -13,6 +25,35 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .manage(AppState::new())
         .plugin(tauri_plugin_opener::init())
+        .setup(|app| {
+            commands::watcher::forward_events(app.handle().clone());
+            let receiver = app.state::<AppState>().events.subscribe();
+            tauri::async_runtime::spawn(record_sessions(receiver));
+            let state = app.state::<AppState>();
+            // 先还原上次异常退出遗留的多显示器布局，再开始监听
+            if let Err(e) = state.layout.recover() {
+                warn!("Failed to recover monitor layout: {}", e);
+            }
+            tauri::async_runtime::spawn(follow_sessions(
+                state.taskbar.clone(),
+                state.events.subscribe(),
+            ));
+            tauri::async_runtime::spawn(guard_tweak_sets(applied_tweak_sets));
+            tauri::async_runtime::spawn(commands::valorant::cfg::reapply_on_launch(
+                state.events.clone(),
+            ));
+            tray::init(app.handle())?;
+            hotkey::init(app.handle())?;
+            tauri::async_runtime::spawn(commands::watcher::auto_start(app.handle().clone()));
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            // 关闭窗口时收进托盘，监听器继续运行
+            if let WindowEvent::CloseRequested { api, .. } = event {
+                api.prevent_close();
+                let _ = window.hide();
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             // 配置命令
             commands::config::load_config,
@@ -22,17 +63,45 @@ pub fn run() {
             commands::display::scan_monitors,
             commands::display::get_monitors_map,
             commands::display::select_monitor,
+            commands::display::try_display_mode,
+            commands::display::confirm_display_mode,
+            commands::display::cancel_display_mode,
+            commands::display::apply_profile_mode,
+            commands::display::restore_monitor,
+            commands::display::get_current_modes,
+            // 分辨率方案
+            commands::profile::get_profiles,
+            commands::profile::switch_profile,
             // 监听命令
             commands::watcher::toggle_watching,
             commands::watcher::get_watching_status,
             commands::watcher::get_gaming_status,
+            commands::watcher::test_process_matcher,
+            // 会话历史
+            commands::history::get_session_history,
+            commands::history::get_daily_playtime,
+            commands::history::get_profile_sessions,
+            commands::history::get_failed_switches,
             // 无畏契约
             commands::valorant::init::scan_game_path,
+            commands::valorant::init::discover_installs,
+            commands::valorant::init::check_install,
             commands::valorant::init::create_preset_watcher,
             commands::valorant::init::start_game,
+            commands::valorant::init::launch_game,
             commands::valorant::init::hide_windows_taskbar,
             commands::valorant::cfg::modify_cfg_file,
             commands::valorant::cfg::restore_file_pemission,
+            commands::valorant::cfg::get_protection_status,
+            commands::valorant::cfg::set_protection_policy,
+            commands::valorant::cfg::check_cfg_drift,
+            // 画质预设
+            commands::graphics::get_graphics_presets,
+            commands::graphics::read_graphics_settings,
+            commands::graphics::apply_graphics_preset,
+            commands::graphics::save_graphics_preset,
+            commands::graphics::export_graphics_preset,
+            commands::graphics::import_graphics_preset,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,272 @@
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use tauri::{
+    menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
+    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
+    AppHandle, Manager, Wry,
+};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::warn;
+
+use crate::{
+    commands::{
+        profile::switch_to_profile,
+        valorant::{cfg::modify_cfg_file, init::start_game},
+        watcher::{apply_mode_now, restore_display_mode, stop_watching, toggle_watching},
+    },
+    configs::{app_config::AppConfig, app_state::AppState},
+    utils::watcher_manager::WatcherEvent,
+};
+
+const TRAY_ID: &str = "main";
+const TOGGLE_WATCHING: &str = "toggle_watching";
+const APPLY_NOW: &str = "apply_now";
+const RESTORE_NOW: &str = "restore_now";
+const MODIFY_CFG: &str = "modify_cfg";
+const START_GAME: &str = "start_game";
+const SHOW_WINDOW: &str = "show_window";
+const QUIT: &str = "quit";
+/// 方案菜单项的 id 为前缀加方案名称
+const PROFILE_PREFIX: &str = "profile:";
+
+/// 托盘菜单展示的监听状态
+#[derive(Debug, Default)]
+struct TrayStatus {
+    watching: bool,
+    game: Option<String>,
+}
+
+impl TrayStatus {
+    /// 根据监听器事件更新状态，返回菜单是否需要刷新
+    fn handle(&mut self, event: &WatcherEvent) -> bool {
+        match event {
+            WatcherEvent::WatchingStarted { .. } => self.watching = true,
+            WatcherEvent::WatchingStopped { .. } => {
+                self.watching = false;
+                self.game = None;
+            }
+            WatcherEvent::GameDetected { pid, exe } => {
+                let name = Path::new(exe)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| exe.clone());
+                self.game = Some(format!("{} ({})", name, pid));
+            }
+            WatcherEvent::GameExited { .. } => self.game = None,
+            // 方案勾选状态从配置读取，只需刷新
+            WatcherEvent::ProfileSwitched { .. } => {}
+            _ => return false,
+        }
+        true
+    }
+}
+
+pub fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+fn build_menu(app: &AppHandle, status: &TrayStatus) -> tauri::Result<Menu<Wry>> {
+    let watching_text = if status.watching {
+        "监听中"
+    } else {
+        "未监听"
+    };
+    let status_item = MenuItem::with_id(
+        app,
+        "status",
+        format!("状态：{}", watching_text),
+        false,
+        None::<&str>,
+    )?;
+    let game_item = MenuItem::with_id(
+        app,
+        "game",
+        format!("游戏：{}", status.game.as_deref().unwrap_or("未检测到")),
+        false,
+        None::<&str>,
+    )?;
+    let toggle_item = MenuItem::with_id(
+        app,
+        TOGGLE_WATCHING,
+        if status.watching {
+            "停止监听"
+        } else {
+            "开始监听"
+        },
+        true,
+        None::<&str>,
+    )?;
+
+    let watcher_config = AppConfig::load_app_config().map(|c| c.watcher).ok();
+    let active_profile = watcher_config
+        .as_ref()
+        .and_then(|c| c.active_profile.clone());
+    let profile_items = watcher_config
+        .map(|c| c.profiles)
+        .unwrap_or_default()
+        .iter()
+        .map(|profile| {
+            CheckMenuItem::with_id(
+                app,
+                format!("{}{}", PROFILE_PREFIX, profile.name),
+                &profile.name,
+                true,
+                active_profile.as_deref() == Some(profile.name.as_str()),
+                None::<&str>,
+            )
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let profile_refs = profile_items
+        .iter()
+        .map(|item| item as &dyn IsMenuItem<Wry>)
+        .collect::<Vec<_>>();
+    let profile_menu = Submenu::with_id_and_items(
+        app,
+        "profiles",
+        "切换方案",
+        !profile_refs.is_empty(),
+        &profile_refs,
+    )?;
+
+    let apply_item = MenuItem::with_id(app, APPLY_NOW, "立即应用拉伸分辨率", true, None::<&str>)?;
+    let restore_item = MenuItem::with_id(app, RESTORE_NOW, "立即还原分辨率", true, None::<&str>)?;
+    let cfg_item = MenuItem::with_id(app, MODIFY_CFG, "修改游戏配置文件", true, None::<&str>)?;
+    let start_item = MenuItem::with_id(app, START_GAME, "启动游戏", true, None::<&str>)?;
+    let show_item = MenuItem::with_id(app, SHOW_WINDOW, "显示主窗口", true, None::<&str>)?;
+    let quit_item = MenuItem::with_id(app, QUIT, "退出", true, None::<&str>)?;
+
+    Menu::with_items(
+        app,
+        &[
+            &status_item,
+            &game_item,
+            &PredefinedMenuItem::separator(app)?,
+            &toggle_item,
+            &profile_menu,
+            &PredefinedMenuItem::separator(app)?,
+            &apply_item,
+            &restore_item,
+            &cfg_item,
+            &start_item,
+            &PredefinedMenuItem::separator(app)?,
+            &show_item,
+            &quit_item,
+        ],
+    )
+}
+
+fn refresh(app: &AppHandle, status: &Mutex<TrayStatus>) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let status = status.lock().unwrap();
+    match build_menu(app, &status) {
+        Ok(menu) => {
+            if let Err(e) = tray.set_menu(Some(menu)) {
+                warn!("Failed to update tray menu: {}", e);
+            }
+        }
+        Err(e) => warn!("Failed to build tray menu: {}", e),
+    }
+    let tooltip = match (&status.game, status.watching) {
+        (Some(game), _) => format!("FPSEnhancer - {}", game),
+        (None, true) => "FPSEnhancer - 监听中".to_string(),
+        (None, false) => "FPSEnhancer".to_string(),
+    };
+    let _ = tray.set_tooltip(Some(tooltip));
+}
+
+async fn run_action(app: &AppHandle, id: &str) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    match id {
+        TOGGLE_WATCHING => toggle_watching(state).await.map(|_| ()),
+        APPLY_NOW => apply_mode_now(&state).await,
+        RESTORE_NOW => restore_display_mode(&state),
+        MODIFY_CFG => modify_cfg_file(),
+        START_GAME => start_game().await,
+        QUIT => {
+            // 退出前还原分辨率、任务栏和多显示器布局
+            let result = stop_watching(&state).await;
+            if let Err(e) = state.taskbar.lock().await.release() {
+                warn!("Failed to restore taskbar: {}", e);
+            }
+            if let Err(e) = state.layout.release() {
+                warn!("Failed to restore monitor layout: {}", e);
+            }
+            app.exit(0);
+            result
+        }
+        _ => match id.strip_prefix(PROFILE_PREFIX) {
+            Some(name) => switch_to_profile(&state, name).await,
+            None => Ok(()),
+        },
+    }
+}
+
+/// 创建托盘图标，并随监听器事件刷新菜单
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    let status = Arc::new(Mutex::new(TrayStatus::default()));
+    let menu = build_menu(app, &status.lock().unwrap())?;
+
+    let menu_status = status.clone();
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("FPSEnhancer")
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(move |app, event| {
+            let id = event.id().as_ref().to_string();
+            if id == SHOW_WINDOW {
+                show_main_window(app);
+                return;
+            }
+            let app = app.clone();
+            let status = menu_status.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = run_action(&app, &id).await {
+                    warn!("Tray action {} failed: {}", id, e);
+                }
+                refresh(&app, &status);
+            });
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                show_main_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+
+    let app = app.clone();
+    let mut receiver = app.state::<AppState>().events.subscribe();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    let changed = status.lock().unwrap().handle(&event);
+                    if changed {
+                        refresh(&app, &status);
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Tray event receiver lagged, {} events skipped", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+    Ok(())
+}
@@ -1,6 +1,20 @@
 pub mod command_manager;
 pub mod constant_manager;
+pub mod discovery_manager;
 pub mod display_manager;
+pub mod file_manager;
+pub mod graphics_manager;
+pub mod history_manager;
+pub mod hotkey_manager;
+pub mod launch_manager;
+pub mod layout_manager;
 pub mod logger_manager;
-pub mod text_manager;
+pub mod matcher_manager;
+pub mod session_manager;
+pub mod shortcut_manager;
+pub mod taskbar_manager;
+#[cfg(test)]
+pub(crate) mod test_util;
+pub mod tweak_manager;
 pub mod watcher_manager;
+pub mod window_manager;
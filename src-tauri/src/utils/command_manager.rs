@@ -1,29 +1,247 @@
-use std::{error::Error, path::PathBuf, process::Command};
+use std::{
+    io::{self, Read},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
 
 use sysinfo::System;
+use thiserror::Error;
 use tracing::debug;
 
-pub fn run_command(command: &[&str]) -> Result<String, Box<dyn Error>> {
-    let output = Command::new("cmd").arg("/C").args(command).output()?;
-    debug!("Running command: {:?}", command);
-    if output.status.success() {
-        let stdout = String::from_utf8(output.stdout)?;
-        debug!("Command output: {}", stdout);
-        Ok(stdout)
+#[derive(Error, Debug)]
+pub enum CommandError {
+    #[error("Failed to start {program}: {source}")]
+    Spawn { program: String, source: io::Error },
+    #[error("{program} did not exit within {timeout:?}")]
+    Timeout { program: String, timeout: Duration },
+    #[error("{program} exited with code {code:?}: {stderr}")]
+    Failed {
+        program: String,
+        code: Option<i32>,
+        stderr: String,
+    },
+    #[error("Failed to wait for process: {0}")]
+    Io(#[from] io::Error),
+}
+
+#[derive(Debug, Clone)]
+pub struct CommandOutput {
+    pub code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// 不弹出控制台窗口
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+/// CreateProcess 遇到需要管理员权限的程序时返回的错误码
+#[cfg(windows)]
+const ERROR_ELEVATION_REQUIRED: i32 = 740;
+
+/// 按 Windows 命令行规则给单个参数加引号，结果可被 CommandLineToArgvW 原样解析
+///
+/// 只在需要把参数拼成一条命令行时使用（如 ShellExecuteW）；`Command::args` 会自行处理引号。
+pub fn quote_arg(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '\n', '"']) {
+        return arg.to_string();
+    }
+    let mut quoted = String::from('"');
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                // 引号前的反斜杠需要翻倍，引号本身再转义
+                quoted.push_str(&"\\".repeat(backslashes * 2 + 1));
+                quoted.push('"');
+                backslashes = 0;
+            }
+            _ => {
+                quoted.push_str(&"\\".repeat(backslashes));
+                quoted.push(c);
+                backslashes = 0;
+            }
+        }
+    }
+    // 结尾的反斜杠紧挨着闭合引号，同样需要翻倍
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+/// 先按 UTF-8 解码，失败时按 OEM 代码页解码（中文系统上控制台程序输出 GBK）
+pub fn decode_output(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => decode_oem(bytes),
+    }
+}
+
+#[cfg(windows)]
+fn decode_oem(bytes: &[u8]) -> String {
+    decode_code_page(bytes, windows::Win32::Globalization::CP_OEMCP)
+}
+
+#[cfg(windows)]
+fn decode_code_page(bytes: &[u8], code_page: u32) -> String {
+    use windows::Win32::Globalization::{MultiByteToWideChar, MULTI_BYTE_TO_WIDE_CHAR_FLAGS};
+
+    let flags = MULTI_BYTE_TO_WIDE_CHAR_FLAGS(0);
+    let len = unsafe { MultiByteToWideChar(code_page, flags, bytes, None) };
+    if len <= 0 {
+        return String::from_utf8_lossy(bytes).into_owned();
+    }
+    let mut wide = vec![0u16; len as usize];
+    unsafe { MultiByteToWideChar(code_page, flags, bytes, Some(&mut wide)) };
+    String::from_utf16_lossy(&wide)
+}
+
+#[cfg(not(windows))]
+fn decode_oem(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+fn new_command(program: &Path, args: &[&str]) -> Command {
+    let mut command = Command::new(program);
+    command.args(args);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    command
+}
+
+fn read_in_background<R: Read + Send + 'static>(pipe: Option<R>) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+        buffer
+    })
+}
+
+/// 直接运行程序并等待其退出，不经过 cmd；超时后结束该进程
+pub fn run_program(
+    program: &Path,
+    args: &[&str],
+    timeout: Duration,
+) -> Result<CommandOutput, CommandError> {
+    let name = program.to_string_lossy().into_owned();
+    debug!("Running program: {} {:?}", name, args);
+    let mut child = new_command(program, args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|source| CommandError::Spawn {
+            program: name.clone(),
+            source,
+        })?;
+    // 在后台读取输出，避免管道写满导致子进程阻塞
+    let stdout = read_in_background(child.stdout.take());
+    let stderr = read_in_background(child.stderr.take());
+
+    let started_at = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if started_at.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(CommandError::Timeout {
+                program: name,
+                timeout,
+            });
+        }
+        thread::sleep(Duration::from_millis(20));
+    };
+
+    let output = CommandOutput {
+        code: status.code(),
+        stdout: decode_output(&stdout.join().unwrap_or_default()),
+        stderr: decode_output(&stderr.join().unwrap_or_default()),
+    };
+    debug!("Program output: {:?}", output);
+    if status.success() {
+        Ok(output)
     } else {
-        let stderr = String::from_utf8(output.stderr)?;
-        debug!("Command error output: {}", stderr);
-        Err(format!("Command failed: {}", stderr).into())
+        Err(CommandError::Failed {
+            program: name,
+            code: output.code,
+            stderr: output.stderr,
+        })
+    }
+}
+
+/// 启动程序后立即返回，不等待其退出；工作目录为程序所在目录
+///
+/// 需要管理员权限的程序（如部分登录器）无法由 CreateProcess 直接启动，此时改用 ShellExecuteW 以触发 UAC 提示。
+pub fn spawn_detached(program: &Path, args: &[&str]) -> Result<(), CommandError> {
+    let name = program.to_string_lossy().into_owned();
+    let mut command = new_command(program, args);
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    if let Some(dir) = program.parent().filter(|d| !d.as_os_str().is_empty()) {
+        command.current_dir(dir);
+    }
+    match command.spawn() {
+        Ok(child) => {
+            debug!("Started {} {:?} (pid {})", name, args, child.id());
+            Ok(())
+        }
+        #[cfg(windows)]
+        Err(e) if e.raw_os_error() == Some(ERROR_ELEVATION_REQUIRED) => {
+            debug!("{} requires elevation, retrying with ShellExecuteW", name);
+            shell_execute(program, args)
+        }
+        Err(source) => Err(CommandError::Spawn {
+            program: name,
+            source,
+        }),
     }
 }
 
-pub fn run_command_detached(command: &[&str]) -> Result<(), Box<dyn Error>> {
-    Command::new("cmd")
-        .arg("/C")
-        .args(command)
-        .spawn()?;
-    debug!("Running detached command: {:?}", command);
-    Ok(())
+#[cfg(windows)]
+fn shell_execute(program: &Path, args: &[&str]) -> Result<(), CommandError> {
+    use windows::{
+        core::{w, HSTRING, PCWSTR},
+        Win32::UI::{Shell::ShellExecuteW, WindowsAndMessaging::SW_SHOWNORMAL},
+    };
+
+    let file = HSTRING::from(program.as_os_str());
+    let parameters = HSTRING::from(
+        args.iter()
+            .map(|a| quote_arg(a))
+            .collect::<Vec<_>>()
+            .join(" "),
+    );
+    let directory = HSTRING::from(program.parent().unwrap_or(Path::new("")).as_os_str());
+    let result = unsafe {
+        ShellExecuteW(
+            None,
+            w!("open"),
+            PCWSTR(file.as_ptr()),
+            PCWSTR(parameters.as_ptr()),
+            PCWSTR(directory.as_ptr()),
+            SW_SHOWNORMAL,
+        )
+    };
+    // 返回值大于 32 表示成功
+    if result.0 as isize > 32 {
+        Ok(())
+    } else {
+        Err(CommandError::Spawn {
+            program: program.to_string_lossy().into_owned(),
+            source: io::Error::last_os_error(),
+        })
+    }
 }
 
 pub fn get_running_process_path(process_name: &str) -> Option<PathBuf> {
@@ -72,3 +290,45 @@ pub fn get_running_process_path(process_name: &str) -> Option<PathBuf> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_args_are_not_quoted() {
+        assert_eq!(
+            quote_arg("--launch-product=valorant"),
+            "--launch-product=valorant"
+        );
+        assert_eq!(quote_arg(r"C:\Games\A&B^C%D"), r"C:\Games\A&B^C%D");
+    }
+
+    #[test]
+    fn args_with_spaces_or_quotes_are_quoted() {
+        assert_eq!(quote_arg(""), r#""""#);
+        assert_eq!(
+            quote_arg(r"C:\Program Files\Game"),
+            r#""C:\Program Files\Game""#
+        );
+        assert_eq!(quote_arg(r#"say "hi""#), r#""say \"hi\"""#);
+        // 结尾的反斜杠不能转义闭合引号
+        assert_eq!(quote_arg(r"C:\Program Files\"), r#""C:\Program Files\\""#);
+        assert_eq!(quote_arg(r#"a\"b c"#), r#""a\\\"b c""#);
+    }
+
+    #[test]
+    fn output_falls_back_when_not_utf8() {
+        assert_eq!(decode_output("拒绝访问".as_bytes()), "拒绝访问");
+        assert!(!decode_output(&[0xbe, 0xdc, 0xbe, 0xf8]).is_empty());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn gbk_output_is_decoded() {
+        // 简体中文系统的控制台输出为 GBK（代码页 936）
+        let gbk = [0xce, 0xde, 0xce, 0xb7, 0xc6, 0xf5, 0xd4, 0xbc];
+        assert!(std::str::from_utf8(&gbk).is_err());
+        assert_eq!(decode_code_page(&gbk, 936), "无畏契约");
+    }
+}
@@ -10,3 +10,8 @@ pub static CONFIG_FILE: LazyLock<PathBuf> = LazyLock::new(|| {
         .to_path_buf()
         .join("config.toml")
 });
+pub static HISTORY_FILE: LazyLock<PathBuf> = LazyLock::new(|| WORK_DIR.join("sessions.jsonl"));
+pub static TASKBAR_STATE_FILE: LazyLock<PathBuf> =
+    LazyLock::new(|| WORK_DIR.join("taskbar_state.json"));
+pub static MONITOR_LAYOUT_FILE: LazyLock<PathBuf> =
+    LazyLock::new(|| WORK_DIR.join("monitor_layout.json"));
@@ -0,0 +1,634 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::Serialize;
+use tracing::debug;
+
+use crate::utils::{
+    command_manager::run_program, matcher_manager::normalize_path,
+    shortcut_manager::read_shortcut_target,
+};
+
+/// 线索的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum EvidenceSource {
+    /// 常见安装目录
+    InstallRoot,
+    /// 登录器写下的清单或配置文件
+    LauncherManifest,
+    /// 桌面和开始菜单的快捷方式
+    Shortcut,
+    /// 控制面板卸载信息
+    UninstallRegistry,
+}
+
+impl EvidenceSource {
+    /// 来源越可靠权重越高
+    fn weight(self) -> u32 {
+        match self {
+            EvidenceSource::UninstallRegistry => 40,
+            EvidenceSource::LauncherManifest => 35,
+            EvidenceSource::Shortcut => 25,
+            EvidenceSource::InstallRoot => 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum FindingKind {
+    Launcher,
+    /// 游戏目录或其中的任意文件，会归一到游戏根目录
+    Game,
+}
+
+/// 单个探测器给出的线索
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub kind: FindingKind,
+    pub path: PathBuf,
+    pub source: EvidenceSource,
+    /// 线索的具体出处，如注册表键或快捷方式路径
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Evidence {
+    pub source: EvidenceSource,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Candidate {
+    pub path: String,
+    pub score: u32,
+    pub evidence: Vec<Evidence>,
+}
+
+/// 按得分从高到低排列的候选路径
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DiscoveryReport {
+    pub launchers: Vec<Candidate>,
+    pub games: Vec<Candidate>,
+}
+
+/// 一种离线查找安装位置的方式
+pub trait InstallProbe: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn probe(&self) -> Vec<Finding>;
+}
+
+/// 游戏根目录的判定规则
+#[derive(Debug, Clone)]
+pub struct GameLayout {
+    /// 根目录下必须存在的任一条目，如 `VALORANT.exe`、`ShooterGame`
+    pub markers: Vec<String>,
+    /// 指向上层目录时依次尝试的子目录，如 `live`
+    pub subdirs: Vec<PathBuf>,
+}
+
+impl GameLayout {
+    fn is_root(&self, dir: &Path) -> bool {
+        self.markers.iter().any(|m| dir.join(m).exists())
+    }
+
+    /// 从线索路径推出游戏根目录：先向上找，再尝试子目录
+    pub fn resolve_root(&self, path: &Path) -> Option<PathBuf> {
+        if let Some(root) = path.ancestors().find(|dir| self.is_root(dir)) {
+            return Some(root.to_path_buf());
+        }
+        self.subdirs
+            .iter()
+            .map(|sub| path.join(sub))
+            .find(|dir| self.is_root(dir))
+    }
+}
+
+/// 合并所有探测器的线索，丢弃不存在的路径后按得分排序
+pub fn discover(probes: &[Box<dyn InstallProbe>], layout: &GameLayout) -> DiscoveryReport {
+    let mut launchers: HashMap<String, (PathBuf, Vec<Evidence>)> = HashMap::new();
+    let mut games: HashMap<String, (PathBuf, Vec<Evidence>)> = HashMap::new();
+
+    for probe in probes {
+        let findings = probe.probe();
+        debug!("Probe {} found {} entries", probe.name(), findings.len());
+        for finding in findings {
+            let (groups, path) = match finding.kind {
+                FindingKind::Launcher if finding.path.is_file() => (&mut launchers, finding.path),
+                FindingKind::Launcher => continue,
+                FindingKind::Game => match layout.resolve_root(&finding.path) {
+                    Some(root) => (&mut games, root),
+                    None => continue,
+                },
+            };
+            let key = normalize_path(&path.to_string_lossy());
+            let (_, evidence) = groups.entry(key).or_insert_with(|| (path, Vec::new()));
+            evidence.push(Evidence {
+                source: finding.source,
+                detail: finding.detail,
+            });
+        }
+    }
+
+    DiscoveryReport {
+        launchers: rank(launchers),
+        games: rank(games),
+    }
+}
+
+/// 每种来源只计一次分
+fn rank(groups: HashMap<String, (PathBuf, Vec<Evidence>)>) -> Vec<Candidate> {
+    let mut candidates = groups
+        .into_values()
+        .map(|(path, evidence)| {
+            let mut sources = evidence.iter().map(|e| e.source).collect::<Vec<_>>();
+            sources.sort_by_key(|s| s.weight());
+            sources.dedup();
+            let score = sources.iter().map(|s| s.weight()).sum();
+            Candidate {
+                path: path.to_string_lossy().into_owned(),
+                score,
+                evidence,
+            }
+        })
+        .collect::<Vec<_>>();
+    candidates.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+    candidates
+}
+
+/// 当前存在的盘符根目录
+pub fn available_drives() -> Vec<PathBuf> {
+    ('A'..='Z')
+        .map(|letter| PathBuf::from(format!("{}:\\", letter)))
+        .filter(|root| root.exists())
+        .collect()
+}
+
+/// 在每个根目录下检查常见的相对安装路径
+pub struct InstallRootProbe {
+    pub roots: Vec<PathBuf>,
+    pub launchers: Vec<PathBuf>,
+    pub games: Vec<PathBuf>,
+}
+
+impl InstallProbe for InstallRootProbe {
+    fn name(&self) -> &'static str {
+        "install-root"
+    }
+
+    fn probe(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for root in &self.roots {
+            let kinds = self
+                .launchers
+                .iter()
+                .map(|p| (FindingKind::Launcher, p))
+                .chain(self.games.iter().map(|p| (FindingKind::Game, p)));
+            for (kind, relative) in kinds {
+                let path = root.join(relative);
+                if path.exists() {
+                    findings.push(Finding {
+                        kind,
+                        detail: format!("Found at {}", path.display()),
+                        path,
+                        source: EvidenceSource::InstallRoot,
+                    });
+                }
+            }
+        }
+        findings
+    }
+}
+
+/// 登录器清单中的路径字段
+#[derive(Debug, Clone)]
+pub struct ManifestEntry {
+    pub kind: FindingKind,
+    pub file: PathBuf,
+    /// JSON 或 YAML 中的键名
+    pub key: String,
+}
+
+/// 从 `"key": "value"` 或 `key: "value"` 形式的行中取值
+pub fn manifest_value(content: &str, key: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let line = line.trim().trim_end_matches(',');
+        let rest = line
+            .strip_prefix(&format!("\"{}\"", key))
+            .or_else(|| line.strip_prefix(key))?;
+        let value = rest.trim_start().strip_prefix(':')?.trim();
+        let value = value.trim_matches(|c| c == '"' || c == '\'');
+        // JSON 中的反斜杠被转义过
+        (!value.is_empty()).then(|| value.replace("\\\\", "\\"))
+    })
+}
+
+pub struct ManifestProbe {
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl InstallProbe for ManifestProbe {
+    fn name(&self) -> &'static str {
+        "launcher-manifest"
+    }
+
+    fn probe(&self) -> Vec<Finding> {
+        self.entries
+            .iter()
+            .filter_map(|entry| {
+                let content = fs::read_to_string(&entry.file).ok()?;
+                let value = manifest_value(&content, &entry.key)?;
+                Some(Finding {
+                    kind: entry.kind,
+                    path: PathBuf::from(value),
+                    source: EvidenceSource::LauncherManifest,
+                    detail: format!("{} in {}", entry.key, entry.file.display()),
+                })
+            })
+            .collect()
+    }
+}
+
+/// 在桌面和开始菜单中查找名称包含关键字的快捷方式
+pub struct ShortcutProbe {
+    pub dirs: Vec<PathBuf>,
+    /// 快捷方式名称关键字，不区分大小写
+    pub keywords: Vec<String>,
+    /// 登录器的文件名（小写），其余目标视为游戏目录中的文件
+    pub launcher_names: Vec<String>,
+}
+
+fn collect_shortcuts(dir: &Path, depth: usize, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.is_dir() && depth > 0 {
+            collect_shortcuts(&path, depth - 1, found);
+        } else if path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("lnk"))
+        {
+            found.push(path);
+        }
+    }
+}
+
+impl InstallProbe for ShortcutProbe {
+    fn name(&self) -> &'static str {
+        "shortcut"
+    }
+
+    fn probe(&self) -> Vec<Finding> {
+        let mut shortcuts = Vec::new();
+        for dir in &self.dirs {
+            collect_shortcuts(dir, 2, &mut shortcuts);
+        }
+        shortcuts
+            .into_iter()
+            .filter(|link| {
+                let stem = link
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().to_lowercase())
+                    .unwrap_or_default();
+                self.keywords
+                    .iter()
+                    .any(|k| stem.contains(&k.to_lowercase()))
+            })
+            .filter_map(|link| {
+                let target = PathBuf::from(read_shortcut_target(&link)?);
+                let file_name = target.file_name()?.to_string_lossy().to_lowercase();
+                let kind = if self.launcher_names.contains(&file_name) {
+                    FindingKind::Launcher
+                } else {
+                    FindingKind::Game
+                };
+                Some(Finding {
+                    kind,
+                    path: target,
+                    source: EvidenceSource::Shortcut,
+                    detail: format!("Shortcut {}", link.display()),
+                })
+            })
+            .collect()
+    }
+}
+
+/// `reg query /s` 输出中的一个键及其值
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RegistryEntry {
+    pub key: String,
+    pub values: HashMap<String, String>,
+}
+
+/// 解析 `reg query` 的输出，值行形如 `    Name    REG_SZ    Data`
+pub fn parse_reg_query(output: &str) -> Vec<RegistryEntry> {
+    let mut entries = Vec::new();
+    let mut current: Option<RegistryEntry> = None;
+    for line in output.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if !line.starts_with(' ') {
+            entries.extend(current.take());
+            current = Some(RegistryEntry {
+                key: line.trim().to_string(),
+                ..Default::default()
+            });
+            continue;
+        }
+        let mut parts = line.trim().splitn(3, "    ");
+        let (Some(name), Some(kind), Some(data)) = (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        if let (Some(entry), true) = (current.as_mut(), kind.starts_with("REG_")) {
+            entry
+                .values
+                .insert(name.trim().to_string(), data.trim().to_string());
+        }
+    }
+    entries.extend(current);
+    entries
+}
+
+/// 程序路径可能带引号和参数，如 `"C:\a.exe" --uninstall` 或 `C:\a.exe,0`
+fn registry_path(value: &str) -> PathBuf {
+    let value = value.trim();
+    let path = match value.strip_prefix('"') {
+        Some(rest) => rest.split('"').next().unwrap_or(rest),
+        None => value.split(',').next().unwrap_or(value),
+    };
+    PathBuf::from(path)
+}
+
+/// 小写的文件名，注册表中的路径使用 `\`，在其他平台上也按它拆分
+fn file_name_lower(path: &Path) -> String {
+    path.to_string_lossy()
+        .rsplit(['\\', '/'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// 从卸载信息中挑出显示名称包含关键字的程序
+///
+/// 只有文件名在 `launcher_names`（小写）中的程序视为登录器，游戏本体和卸载程序只作为游戏目录的线索。
+pub fn uninstall_findings(
+    entries: &[RegistryEntry],
+    keywords: &[String],
+    launcher_names: &[String],
+) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for entry in entries {
+        let Some(display_name) = entry.values.get("DisplayName") else {
+            continue;
+        };
+        let name = display_name.to_lowercase();
+        if !keywords.iter().any(|k| name.contains(&k.to_lowercase())) {
+            continue;
+        }
+        let detail = format!("{} ({})", display_name, entry.key);
+        if let Some(location) = entry.values.get("InstallLocation") {
+            findings.push(Finding {
+                kind: FindingKind::Game,
+                path: PathBuf::from(location),
+                source: EvidenceSource::UninstallRegistry,
+                detail: detail.clone(),
+            });
+        }
+        for name in ["DisplayIcon", "UninstallString"] {
+            if let Some(value) = entry.values.get(name) {
+                let path = registry_path(value);
+                let kind = if launcher_names.contains(&file_name_lower(&path)) {
+                    FindingKind::Launcher
+                } else {
+                    FindingKind::Game
+                };
+                findings.push(Finding {
+                    kind,
+                    path,
+                    source: EvidenceSource::UninstallRegistry,
+                    detail: detail.clone(),
+                });
+            }
+        }
+    }
+    findings
+}
+
+const UNINSTALL_KEYS: [&str; 3] = [
+    r"HKLM\SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall",
+    r"HKLM\SOFTWARE\WOW6432Node\Microsoft\Windows\CurrentVersion\Uninstall",
+    r"HKCU\SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall",
+];
+
+/// 通过 `reg.exe` 读取卸载信息
+pub struct UninstallRegistryProbe {
+    pub keywords: Vec<String>,
+    /// 登录器的文件名（小写），其余程序视为游戏目录中的文件
+    pub launcher_names: Vec<String>,
+}
+
+impl InstallProbe for UninstallRegistryProbe {
+    fn name(&self) -> &'static str {
+        "uninstall-registry"
+    }
+
+    fn probe(&self) -> Vec<Finding> {
+        let mut entries = Vec::new();
+        for key in UNINSTALL_KEYS {
+            match run_program(
+                Path::new("reg.exe"),
+                &["query", key, "/s"],
+                Duration::from_secs(10),
+            ) {
+                Ok(output) => entries.extend(parse_reg_query(&output.stdout)),
+                Err(e) => debug!("Failed to query {}: {}", key, e),
+            }
+        }
+        uninstall_findings(&entries, &self.keywords, &self.launcher_names)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_util::TempDir;
+
+    struct FixedProbe(Vec<Finding>);
+
+    impl InstallProbe for FixedProbe {
+        fn name(&self) -> &'static str {
+            "fixed"
+        }
+
+        fn probe(&self) -> Vec<Finding> {
+            self.0.clone()
+        }
+    }
+
+    fn finding(kind: FindingKind, path: &Path, source: EvidenceSource) -> Finding {
+        Finding {
+            kind,
+            path: path.to_path_buf(),
+            source,
+            detail: String::new(),
+        }
+    }
+
+    fn layout() -> GameLayout {
+        GameLayout {
+            markers: vec!["VALORANT.exe".to_string(), "ShooterGame".to_string()],
+            subdirs: vec![PathBuf::from("live")],
+        }
+    }
+
+    #[test]
+    fn findings_merge_into_ranked_candidates() {
+        let root = TempDir::new("discovery");
+        let strong = root.join("Riot Games").join("VALORANT").join("live");
+        let weak = root.join("Games").join("VALORANT").join("live");
+        fs::create_dir_all(strong.join("ShooterGame").join("Binaries")).unwrap();
+        fs::create_dir_all(weak.join("ShooterGame")).unwrap();
+        fs::write(strong.join("VALORANT.exe"), b"").unwrap();
+
+        let probes: Vec<Box<dyn InstallProbe>> = vec![
+            Box::new(FixedProbe(vec![
+                // 指向上层目录和游戏内文件的线索都归到同一个根目录
+                finding(
+                    FindingKind::Game,
+                    &root.join("Riot Games").join("VALORANT"),
+                    EvidenceSource::UninstallRegistry,
+                ),
+                finding(
+                    FindingKind::Game,
+                    &strong.join("ShooterGame").join("Binaries"),
+                    EvidenceSource::Shortcut,
+                ),
+                finding(FindingKind::Game, &weak, EvidenceSource::InstallRoot),
+            ])),
+            Box::new(FixedProbe(vec![
+                finding(
+                    FindingKind::Game,
+                    &strong.join("VALORANT.exe"),
+                    EvidenceSource::Shortcut,
+                ),
+                finding(
+                    FindingKind::Game,
+                    &root.join("missing"),
+                    EvidenceSource::LauncherManifest,
+                ),
+                finding(
+                    FindingKind::Launcher,
+                    &root.join("missing.exe"),
+                    EvidenceSource::Shortcut,
+                ),
+            ])),
+        ];
+        let report = discover(&probes, &layout());
+
+        assert!(report.launchers.is_empty());
+        let games = report
+            .games
+            .iter()
+            .map(|c| (PathBuf::from(&c.path), c.score, c.evidence.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(games, vec![(strong, 40 + 25, 3), (weak, 20, 1),]);
+    }
+
+    #[test]
+    fn manifest_values_from_json_and_yaml() {
+        let json = "{\n  \"rc_default\": \"C:\\\\Riot Games\\\\Riot Client\\\\RiotClientServices.exe\",\n}";
+        assert_eq!(
+            manifest_value(json, "rc_default").as_deref(),
+            Some(r"C:\Riot Games\Riot Client\RiotClientServices.exe")
+        );
+        let yaml = "product_install_full_path: \"C:/Riot Games/VALORANT/live\"\n";
+        assert_eq!(
+            manifest_value(yaml, "product_install_full_path").as_deref(),
+            Some("C:/Riot Games/VALORANT/live")
+        );
+        assert_eq!(manifest_value(yaml, "product_install_root"), None);
+    }
+
+    #[test]
+    fn uninstall_entries_yield_launcher_and_game() {
+        let output = "\r\nHKEY_LOCAL_MACHINE\\SOFTWARE\\Uninstall\\Other\r\n    DisplayName    REG_SZ    Other\r\n\r\nHKEY_LOCAL_MACHINE\\SOFTWARE\\Uninstall\\VALORANT\r\n    DisplayName    REG_SZ    无畏契约\r\n    InstallLocation    REG_SZ    E:\\Games\\Tencent Games\\VALORANT\r\n    DisplayIcon    REG_SZ    \"E:\\Program Files\\ACLOS\\Launcher\\无畏契约登录器.exe\",0\r\n";
+        let entries = parse_reg_query(output);
+        assert_eq!(entries.len(), 2);
+        let findings = uninstall_findings(
+            &entries,
+            &["无畏契约".to_string()],
+            &["无畏契约登录器.exe".to_string()],
+        );
+        assert_eq!(
+            findings
+                .iter()
+                .map(|f| (f.kind, f.path.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    FindingKind::Game,
+                    PathBuf::from(r"E:\Games\Tencent Games\VALORANT")
+                ),
+                (
+                    FindingKind::Launcher,
+                    PathBuf::from(r"E:\Program Files\ACLOS\Launcher\无畏契约登录器.exe")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn uninstaller_and_game_exe_are_game_evidence() {
+        let output = "HKEY_LOCAL_MACHINE\\SOFTWARE\\Uninstall\\Riot Game valorant.live\r\n    DisplayName    REG_SZ    VALORANT\r\n    DisplayIcon    REG_SZ    C:\\Riot Games\\VALORANT\\live\\VALORANT.exe\r\n    UninstallString    REG_SZ    \"C:\\Riot Games\\Riot Client\\RiotClientServices.exe\" --uninstall-product=valorant\r\n";
+        let mut entries = parse_reg_query(output);
+        // 单独的卸载程序
+        entries[0].values.insert(
+            "UninstallString".to_string(),
+            r#""C:\Riot Games\VALORANT\uninstall.exe" /S"#.to_string(),
+        );
+        let findings = uninstall_findings(
+            &entries,
+            &["valorant".to_string()],
+            &["riotclientservices.exe".to_string()],
+        );
+        assert_eq!(
+            findings
+                .iter()
+                .map(|f| (f.kind, f.path.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    FindingKind::Game,
+                    PathBuf::from(r"C:\Riot Games\VALORANT\live\VALORANT.exe")
+                ),
+                (
+                    FindingKind::Game,
+                    PathBuf::from(r"C:\Riot Games\VALORANT\uninstall.exe")
+                ),
+            ]
+        );
+
+        // 卸载命令指向登录器时仍识别为登录器
+        let entries = parse_reg_query(output);
+        let findings = uninstall_findings(
+            &entries,
+            &["valorant".to_string()],
+            &["riotclientservices.exe".to_string()],
+        );
+        assert_eq!(findings[1].kind, FindingKind::Launcher);
+        assert_eq!(
+            findings[1].path,
+            PathBuf::from(r"C:\Riot Games\Riot Client\RiotClientServices.exe")
+        );
+    }
+}
@@ -1,8 +1,17 @@
-use std::{collections::HashMap, ffi::OsStr, mem, os::windows::ffi::OsStrExt};
+use std::{collections::HashMap, fmt};
+#[cfg(windows)]
+use std::{ffi::OsStr, mem, os::windows::ffi::OsStrExt};
 
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use tracing::{debug, warn};
-use windows::{core::PCWSTR, Win32::Graphics::Gdi::*};
+#[cfg(windows)]
+use tracing::debug;
+use tracing::warn;
+#[cfg(windows)]
+use windows::{
+    core::PCWSTR,
+    Win32::{Foundation::POINTL, Graphics::Gdi::*},
+};
 
 #[derive(Error, Debug)]
 pub enum DisplayError {
@@ -10,9 +19,14 @@ pub enum DisplayError {
     EnumFailed,
     #[error("Failed to change display settings: {0}")]
     ChangeFailed(String),
+    #[error("Display settings are not supported on this platform.")]
+    Unsupported,
+    #[error("Requested {requested}, but the display is running {actual}.")]
+    Mismatch { requested: String, actual: String },
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
 pub struct DisplayMode {
     pub width: u32,
     pub height: u32,
@@ -21,6 +35,12 @@ pub struct DisplayMode {
     pub monitor_name: String,
 }
 
+impl fmt::Display for DisplayMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}@{}Hz", self.width, self.height, self.refresh_rate)
+    }
+}
+
 impl Default for DisplayMode {
     fn default() -> Self {
         let max_bits_result = get_max_bits_per_pixel();
@@ -49,6 +69,27 @@ impl Default for DisplayMode {
     }
 }
 
+/// 显示器在桌面上的位置和状态，主显示器位于原点
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MonitorPlacement {
+    pub mode: DisplayMode,
+    pub x: i32,
+    pub y: i32,
+    pub primary: bool,
+    /// 为 false 时从桌面分离该显示器
+    pub active: bool,
+}
+
+#[cfg(windows)]
+fn to_wide(text: &str) -> Vec<u16> {
+    OsStr::new(text)
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect()
+}
+
+#[cfg(windows)]
 pub fn get_current_display_mode() -> Result<DisplayMode, DisplayError> {
     unsafe {
         let mut devmode: DEVMODEW = mem::zeroed();
@@ -81,6 +122,7 @@ pub fn get_current_display_mode() -> Result<DisplayMode, DisplayError> {
 }
 
 // 修改默认监视器的显示模式
+#[cfg(windows)]
 pub fn change_display_mode(mode: &DisplayMode, permanent: bool) -> Result<(), DisplayError> {
     unsafe {
         let mut devmode: DEVMODEW = mem::zeroed();
@@ -134,14 +176,12 @@ pub fn change_display_mode(mode: &DisplayMode, permanent: bool) -> Result<(), Di
 }
 
 // 修改指定监视器的显示模式
+#[cfg(windows)]
 pub fn change_display_mode_for_monitor(
     mode: &DisplayMode,
     permanent: bool,
 ) -> Result<(), DisplayError> {
-    let device_name_wide: Vec<u16> = OsStr::new(&mode.monitor_name)
-        .encode_wide()
-        .chain(std::iter::once(0))
-        .collect();
+    let device_name_wide = to_wide(&mode.monitor_name);
     debug!("Device name wide: {:?}", device_name_wide);
 
     unsafe {
@@ -206,6 +246,40 @@ pub fn change_display_mode_for_monitor(
     }
 }
 
+// 读取指定监视器当前的显示模式，名称为空时读取默认监视器
+#[cfg(windows)]
+pub fn get_display_mode_for_monitor(monitor_name: &str) -> Result<DisplayMode, DisplayError> {
+    let device_name_wide = to_wide(monitor_name);
+    let device = if monitor_name.is_empty() {
+        PCWSTR::null()
+    } else {
+        PCWSTR::from_raw(device_name_wide.as_ptr())
+    };
+
+    unsafe {
+        let mut devmode: DEVMODEW = mem::zeroed();
+        devmode.dmSize = mem::size_of::<DEVMODEW>() as u16;
+        let result = EnumDisplaySettingsW(device, ENUM_CURRENT_SETTINGS, &mut devmode);
+        debug!(
+            "EnumDisplaySettingsW ({}) result: {:?}",
+            monitor_name, result
+        );
+
+        if result.as_bool() {
+            Ok(DisplayMode {
+                width: devmode.dmPelsWidth,
+                height: devmode.dmPelsHeight,
+                refresh_rate: devmode.dmDisplayFrequency,
+                bits_per_pixel: devmode.dmBitsPerPel,
+                monitor_name: monitor_name.to_string(),
+            })
+        } else {
+            Err(DisplayError::EnumFailed)
+        }
+    }
+}
+
+#[cfg(windows)]
 pub fn restore_default_settings() -> Result<(), DisplayError> {
     unsafe {
         let result = ChangeDisplaySettingsW(None, CDS_TYPE(0));
@@ -222,6 +296,35 @@ pub fn restore_default_settings() -> Result<(), DisplayError> {
     }
 }
 
+// 把指定监视器还原为注册表中保存的模式
+#[cfg(windows)]
+pub fn restore_monitor_settings(monitor_name: &str) -> Result<(), DisplayError> {
+    let device_name_wide = to_wide(monitor_name);
+    unsafe {
+        let result = ChangeDisplaySettingsExW(
+            PCWSTR::from_raw(device_name_wide.as_ptr()),
+            None,
+            None,
+            CDS_TYPE(0),
+            None,
+        );
+        debug!(
+            "ChangeDisplaySettingsExW ({}) result: {:?}",
+            monitor_name, result
+        );
+
+        if result == DISP_CHANGE_SUCCESSFUL {
+            Ok(())
+        } else {
+            Err(DisplayError::ChangeFailed(format!(
+                "Restore {} failed: {:?}",
+                monitor_name, result
+            )))
+        }
+    }
+}
+
+#[cfg(windows)]
 pub fn get_max_bits_per_pixel() -> Result<u32, DisplayError> {
     let mut max_bits: u32 = 0;
 
@@ -260,6 +363,7 @@ pub fn get_max_bits_per_pixel() -> Result<u32, DisplayError> {
     }
 }
 
+#[cfg(windows)]
 pub fn enumerate_monitors() -> HashMap<String, String> {
     let mut monitors = HashMap::new();
 
@@ -312,3 +416,372 @@ pub fn enumerate_monitors() -> HashMap<String, String> {
         monitors
     }
 }
+
+// 读取所有活动显示器的分辨率、桌面位置和是否为主显示器
+#[cfg(windows)]
+pub fn read_monitor_placements() -> Result<Vec<MonitorPlacement>, DisplayError> {
+    let mut placements = Vec::new();
+
+    unsafe {
+        let mut device: DISPLAY_DEVICEW = mem::zeroed();
+        device.cb = mem::size_of::<DISPLAY_DEVICEW>() as u32;
+
+        let mut device_index = 0u32;
+        while EnumDisplayDevicesW(PCWSTR::null(), device_index, &mut device, 0).as_bool() {
+            device_index += 1;
+            if (device.StateFlags & DISPLAY_DEVICE_ACTIVE) == DISPLAY_DEVICE_STATE_FLAGS(0) {
+                continue;
+            }
+            let monitor_name = String::from_utf16_lossy(
+                &device.DeviceName[..device
+                    .DeviceName
+                    .iter()
+                    .position(|&c| c == 0)
+                    .unwrap_or(device.DeviceName.len())],
+            );
+
+            let mut devmode: DEVMODEW = mem::zeroed();
+            devmode.dmSize = mem::size_of::<DEVMODEW>() as u16;
+            let result = EnumDisplaySettingsW(
+                PCWSTR::from_raw(device.DeviceName.as_ptr()),
+                ENUM_CURRENT_SETTINGS,
+                &mut devmode,
+            );
+            debug!(
+                "EnumDisplaySettingsW ({}) result: {:?}",
+                monitor_name, result
+            );
+            if !result.as_bool() {
+                return Err(DisplayError::EnumFailed);
+            }
+
+            let position = devmode.Anonymous1.Anonymous2.dmPosition;
+            placements.push(MonitorPlacement {
+                mode: DisplayMode {
+                    width: devmode.dmPelsWidth,
+                    height: devmode.dmPelsHeight,
+                    refresh_rate: devmode.dmDisplayFrequency,
+                    bits_per_pixel: devmode.dmBitsPerPel,
+                    monitor_name,
+                },
+                x: position.x,
+                y: position.y,
+                primary: (device.StateFlags & DISPLAY_DEVICE_PRIMARY_DEVICE)
+                    != DISPLAY_DEVICE_STATE_FLAGS(0),
+                active: true,
+            });
+        }
+    }
+    Ok(placements)
+}
+
+// 一次性应用所有显示器的布局
+// 切换主显示器和分离显示器只能写入注册表，先逐个写入（CDS_NORESET），最后统一生效
+#[cfg(windows)]
+pub fn apply_monitor_placements(placements: &[MonitorPlacement]) -> Result<(), DisplayError> {
+    // 先写入主显示器，其余显示器的位置相对于它
+    let mut ordered: Vec<&MonitorPlacement> = placements.iter().collect();
+    ordered.sort_by_key(|p| !p.primary);
+
+    unsafe {
+        for placement in ordered {
+            let device_name_wide = to_wide(&placement.mode.monitor_name);
+            let mut devmode: DEVMODEW = mem::zeroed();
+            devmode.dmSize = mem::size_of::<DEVMODEW>() as u16;
+            devmode.dmFields = DM_POSITION | DM_PELSWIDTH | DM_PELSHEIGHT;
+            devmode.Anonymous1.Anonymous2.dmPosition = POINTL {
+                x: placement.x,
+                y: placement.y,
+            };
+            // 宽高为 0 表示分离该显示器
+            if placement.active {
+                devmode.dmPelsWidth = placement.mode.width;
+                devmode.dmPelsHeight = placement.mode.height;
+                devmode.dmDisplayFrequency = placement.mode.refresh_rate;
+                devmode.dmBitsPerPel = placement.mode.bits_per_pixel;
+                devmode.dmFields |= DM_DISPLAYFREQUENCY | DM_BITSPERPEL;
+            }
+
+            let mut flags = CDS_UPDATEREGISTRY | CDS_NORESET;
+            if placement.primary {
+                flags |= CDS_SET_PRIMARY;
+            }
+            let result = ChangeDisplaySettingsExW(
+                PCWSTR::from_raw(device_name_wide.as_ptr()),
+                Some(&devmode),
+                None,
+                flags,
+                None,
+            );
+            debug!(
+                "ChangeDisplaySettingsExW ({}) result: {:?}",
+                placement.mode.monitor_name, result
+            );
+            if result != DISP_CHANGE_SUCCESSFUL {
+                return Err(DisplayError::ChangeFailed(format!(
+                    "{}: {:?}",
+                    placement.mode.monitor_name, result
+                )));
+            }
+        }
+
+        let result = ChangeDisplaySettingsExW(PCWSTR::null(), None, None, CDS_TYPE(0), None);
+        debug!("ChangeDisplaySettingsExW (commit) result: {:?}", result);
+        match result {
+            DISP_CHANGE_SUCCESSFUL => Ok(()),
+            _ => Err(DisplayError::ChangeFailed(format!(
+                "Failed to apply monitor layout: {:?}",
+                result
+            ))),
+        }
+    }
+}
+
+// 其他平台没有 Win32 显示设置，只保证核心逻辑可以编译和测试
+#[cfg(not(windows))]
+pub fn get_current_display_mode() -> Result<DisplayMode, DisplayError> {
+    Err(DisplayError::Unsupported)
+}
+
+#[cfg(not(windows))]
+pub fn change_display_mode(_mode: &DisplayMode, _permanent: bool) -> Result<(), DisplayError> {
+    Err(DisplayError::Unsupported)
+}
+
+#[cfg(not(windows))]
+pub fn change_display_mode_for_monitor(
+    _mode: &DisplayMode,
+    _permanent: bool,
+) -> Result<(), DisplayError> {
+    Err(DisplayError::Unsupported)
+}
+
+#[cfg(not(windows))]
+pub fn get_display_mode_for_monitor(_monitor_name: &str) -> Result<DisplayMode, DisplayError> {
+    Err(DisplayError::Unsupported)
+}
+
+#[cfg(not(windows))]
+pub fn restore_default_settings() -> Result<(), DisplayError> {
+    Err(DisplayError::Unsupported)
+}
+
+#[cfg(not(windows))]
+pub fn restore_monitor_settings(_monitor_name: &str) -> Result<(), DisplayError> {
+    Err(DisplayError::Unsupported)
+}
+
+#[cfg(not(windows))]
+pub fn get_max_bits_per_pixel() -> Result<u32, DisplayError> {
+    Err(DisplayError::Unsupported)
+}
+
+#[cfg(not(windows))]
+pub fn enumerate_monitors() -> HashMap<String, String> {
+    HashMap::new()
+}
+
+#[cfg(not(windows))]
+pub fn read_monitor_placements() -> Result<Vec<MonitorPlacement>, DisplayError> {
+    Err(DisplayError::Unsupported)
+}
+
+#[cfg(not(windows))]
+pub fn apply_monitor_placements(_placements: &[MonitorPlacement]) -> Result<(), DisplayError> {
+    Err(DisplayError::Unsupported)
+}
+
+/// 按监视器名称选择修改默认监视器或指定监视器
+pub fn set_display_mode(mode: &DisplayMode, permanent: bool) -> Result<(), DisplayError> {
+    if mode.monitor_name.is_empty() {
+        change_display_mode(mode, permanent)
+    } else {
+        change_display_mode_for_monitor(mode, permanent)
+    }
+}
+
+/// 分辨率必须一致；刷新率为 0 表示不限，驱动把 59.94Hz 报成 59 时也视为一致
+pub fn mode_matches(requested: &DisplayMode, actual: &DisplayMode) -> bool {
+    requested.width == actual.width
+        && requested.height == actual.height
+        && (requested.refresh_rate == 0
+            || requested.refresh_rate.abs_diff(actual.refresh_rate) <= 1)
+}
+
+/// 名称为空时还原所有监视器，否则只还原指定监视器
+pub fn restore_display_settings(monitor_name: &str) -> Result<(), DisplayError> {
+    if monitor_name.is_empty() {
+        restore_default_settings()
+    } else {
+        restore_monitor_settings(monitor_name)
+    }
+}
+
+/// 每个活动监视器当前的显示模式，读取失败的监视器会被跳过
+pub fn current_display_modes() -> Vec<DisplayMode> {
+    let mut names = enumerate_monitors().into_keys().collect::<Vec<_>>();
+    names.sort();
+    names
+        .iter()
+        .filter_map(|name| match get_display_mode_for_monitor(name) {
+            Ok(mode) => Some(mode),
+            Err(e) => {
+                warn!("Failed to read display mode of {}: {}", name, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// 切换到切换前的模式，读取不到切换前的模式时还原注册表中的设置
+pub fn revert_display_mode(previous: Option<&DisplayMode>) -> Result<(), DisplayError> {
+    match previous {
+        Some(previous) => set_display_mode(previous, false),
+        None => restore_default_settings(),
+    }
+}
+
+/// 切换后重新读取当前模式并与请求比较，不一致时还原，返回切换前的模式
+///
+/// 部分驱动返回成功但实际落在其他模式上，只看返回值可能导致黑屏。
+pub fn apply_display_mode_verified(
+    mode: &DisplayMode,
+    permanent: bool,
+) -> Result<Option<DisplayMode>, DisplayError> {
+    let previous = get_display_mode_for_monitor(&mode.monitor_name).ok();
+    set_display_mode(mode, permanent)?;
+    let actual = get_display_mode_for_monitor(&mode.monitor_name)?;
+    if mode_matches(mode, &actual) {
+        return Ok(previous);
+    }
+    warn!("Requested {}, but the display is running {}", mode, actual);
+    if let Err(e) = revert_display_mode(previous.as_ref()) {
+        warn!("Failed to revert mismatched display mode: {}", e);
+    }
+    Err(DisplayError::Mismatch {
+        requested: mode.to_string(),
+        actual: actual.to_string(),
+    })
+}
+
+/// 手动切换后等待用户确认的模式
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PendingMode {
+    pub id: u64,
+    pub mode: DisplayMode,
+    /// 确认后是否写入注册表
+    pub permanent: bool,
+    pub timeout_ms: u64,
+    /// 超时或取消时还原到的模式
+    #[serde(skip)]
+    pub previous: Option<DisplayMode>,
+}
+
+/// 类似 Windows 显示设置的“保留更改”对话框，同一时间只等待一个模式
+#[derive(Debug, Default)]
+pub struct ModeConfirmation {
+    next_id: u64,
+    pending: Option<PendingMode>,
+}
+
+impl ModeConfirmation {
+    /// 开始等待确认；同一显示器上一个模式还没确认时，还原目标仍是最初的模式
+    ///
+    /// 其他显示器上等待中的模式会被丢弃，调用前应先取出并还原。
+    pub fn begin(
+        &mut self,
+        mode: DisplayMode,
+        previous: Option<DisplayMode>,
+        permanent: bool,
+        timeout_ms: u64,
+    ) -> PendingMode {
+        self.next_id += 1;
+        let previous = match self.pending.take() {
+            Some(pending) if pending.mode.monitor_name == mode.monitor_name => pending.previous,
+            _ => previous,
+        };
+        let pending = PendingMode {
+            id: self.next_id,
+            mode,
+            permanent,
+            timeout_ms,
+            previous,
+        };
+        self.pending = Some(pending.clone());
+        pending
+    }
+
+    /// 取出等待中的模式，指定 id 时只取出对应的那一次
+    pub fn take(&mut self, id: Option<u64>) -> Option<PendingMode> {
+        match (&self.pending, id) {
+            (Some(pending), Some(id)) if pending.id != id => None,
+            _ => self.pending.take(),
+        }
+    }
+
+    pub fn pending(&self) -> Option<&PendingMode> {
+        self.pending.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mode(width: u32, height: u32, refresh_rate: u32) -> DisplayMode {
+        DisplayMode {
+            width,
+            height,
+            refresh_rate,
+            bits_per_pixel: 32,
+            monitor_name: String::new(),
+        }
+    }
+
+    #[test]
+    fn mode_matches_tolerates_rounded_refresh_rate() {
+        assert!(mode_matches(&mode(1440, 1080, 60), &mode(1440, 1080, 59)));
+        assert!(mode_matches(&mode(1440, 1080, 0), &mode(1440, 1080, 144)));
+        assert!(!mode_matches(&mode(1440, 1080, 144), &mode(1440, 1080, 60)));
+        assert!(!mode_matches(&mode(1440, 1080, 60), &mode(1920, 1080, 60)));
+    }
+
+    #[test]
+    fn confirmation_keeps_the_original_mode_across_retries() {
+        let mut confirmation = ModeConfirmation::default();
+        let first = confirmation.begin(
+            mode(1440, 1080, 60),
+            Some(mode(1920, 1080, 60)),
+            false,
+            15_000,
+        );
+        let second = confirmation.begin(
+            mode(1280, 960, 60),
+            Some(mode(1440, 1080, 60)),
+            true,
+            15_000,
+        );
+        assert_ne!(first.id, second.id);
+
+        // 第一次的超时不能取走第二次
+        assert!(confirmation.take(Some(first.id)).is_none());
+        let pending = confirmation.take(Some(second.id)).unwrap();
+        assert_eq!(pending.previous.unwrap().width, 1920);
+        assert!(pending.permanent);
+        assert!(confirmation.pending().is_none());
+        assert!(confirmation.take(None).is_none());
+    }
+
+    #[test]
+    fn confirmation_does_not_carry_over_another_monitor() {
+        let on = |monitor_name: &str, width| DisplayMode {
+            monitor_name: monitor_name.to_string(),
+            ..mode(width, 1080, 60)
+        };
+        let mut confirmation = ModeConfirmation::default();
+        confirmation.begin(on("A", 1440), Some(on("A", 1920)), false, 15_000);
+        let second = confirmation.begin(on("B", 1280), Some(on("B", 2560)), false, 15_000);
+        assert_eq!(second.previous, Some(on("B", 2560)));
+    }
+}
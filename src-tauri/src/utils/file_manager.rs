@@ -0,0 +1,46 @@
+use std::{fs, io, path::Path};
+
+/// 文件是否带只读属性
+pub fn is_read_only(path: &Path) -> io::Result<bool> {
+    Ok(fs::metadata(path)?.permissions().readonly())
+}
+
+/// 设置或清除只读属性，相当于 `attrib +R` / `attrib -R`
+pub fn set_read_only(path: &Path, read_only: bool) -> io::Result<()> {
+    let mut permissions = fs::metadata(path)?.permissions();
+    if permissions.readonly() == read_only {
+        return Ok(());
+    }
+    // Windows 上只切换 FILE_ATTRIBUTE_READONLY，不会像 Unix 那样放开所有用户的写权限
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(read_only);
+    fs::set_permissions(path, permissions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_util::TempDir;
+
+    #[test]
+    fn read_only_round_trip() {
+        let dir = TempDir::new("readonly");
+        let path = dir.join("GameUserSettings.ini");
+        fs::write(&path, "FullscreenMode=2").unwrap();
+
+        set_read_only(&path, true).unwrap();
+        assert!(is_read_only(&path).unwrap());
+        // 重复设置不报错
+        set_read_only(&path, true).unwrap();
+
+        set_read_only(&path, false).unwrap();
+        assert!(!is_read_only(&path).unwrap());
+    }
+
+    #[test]
+    fn missing_file_is_an_error() {
+        let dir = TempDir::new("missing_file");
+        let path = dir.join("GameUserSettings.ini");
+        assert!(set_read_only(&path, true).is_err());
+    }
+}
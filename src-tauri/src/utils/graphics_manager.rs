@@ -0,0 +1,405 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::utils::tweak_manager::Tweak;
+
+const SCALABILITY_SECTION: &str = "ScalabilityGroups";
+const USER_SETTINGS_SECTION: &str = "/Script/ShooterGame.ShooterGameUserSettings";
+
+const TEXTURE_KEY: &str = "sg.TextureQuality";
+// 细节质量对应视距组，特效质量 `sg.EffectsQuality` 不由本工具管理
+const DETAIL_KEY: &str = "sg.ViewDistanceQuality";
+const MATERIAL_KEY: &str = "sg.ShadingQuality";
+const ANTI_ALIASING_KEY: &str = "sg.AntiAliasingQuality";
+const ANISOTROPIC_KEY: &str = "AnisotropicFiltering";
+const BLOOM_KEY: &str = "bEnableBloom";
+const DISTORTION_KEY: &str = "bEnableDistortion";
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum GraphicsError {
+    #[error("Invalid value for {key}: {value}")]
+    InvalidValue { key: String, value: String },
+    #[error("Unsupported anisotropic filtering: {0}x")]
+    InvalidAnisotropic(u32),
+    #[error("Preset name is empty")]
+    EmptyName,
+    #[error("Built-in preset cannot be replaced: {0}")]
+    BuiltinPreset(String),
+}
+
+/// `ScalabilityGroups` 中 0-3 的画质等级
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Quality {
+    Low,
+    #[default]
+    Medium,
+    High,
+    Epic,
+}
+
+impl Quality {
+    fn level(self) -> u8 {
+        self as u8
+    }
+
+    fn from_level(level: u8) -> Option<Self> {
+        [Quality::Low, Quality::Medium, Quality::High, Quality::Epic]
+            .get(level as usize)
+            .copied()
+    }
+}
+
+/// 抗锯齿，对应 `sg.AntiAliasingQuality` 的 0-3
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AntiAliasing {
+    #[default]
+    None,
+    Fxaa,
+    Msaa2x,
+    Msaa4x,
+}
+
+impl AntiAliasing {
+    fn from_level(level: u8) -> Option<Self> {
+        [
+            AntiAliasing::None,
+            AntiAliasing::Fxaa,
+            AntiAliasing::Msaa2x,
+            AntiAliasing::Msaa4x,
+        ]
+        .get(level as usize)
+        .copied()
+    }
+}
+
+const ANISOTROPIC_LEVELS: [u32; 5] = [1, 2, 4, 8, 16];
+
+/// 画质设置，写入 GameUserSettings.ini
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GraphicsSettings {
+    pub texture_quality: Quality,
+    pub detail_quality: Quality,
+    pub material_quality: Quality,
+    pub anti_aliasing: AntiAliasing,
+    /// 各向异性过滤倍数：1、2、4、8、16
+    pub anisotropic_filtering: u32,
+    pub bloom: bool,
+    pub distortion: bool,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        GraphicsSettings {
+            texture_quality: Quality::Medium,
+            detail_quality: Quality::Medium,
+            material_quality: Quality::Medium,
+            anti_aliasing: AntiAliasing::Fxaa,
+            anisotropic_filtering: 4,
+            bloom: true,
+            distortion: true,
+        }
+    }
+}
+
+fn bool_value(value: bool) -> &'static str {
+    if value {
+        "True"
+    } else {
+        "False"
+    }
+}
+
+impl GraphicsSettings {
+    pub fn validate(&self) -> Result<(), GraphicsError> {
+        if !ANISOTROPIC_LEVELS.contains(&self.anisotropic_filtering) {
+            return Err(GraphicsError::InvalidAnisotropic(
+                self.anisotropic_filtering,
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn tweaks(&self) -> Vec<Tweak> {
+        vec![
+            Tweak::in_section(
+                SCALABILITY_SECTION,
+                TEXTURE_KEY,
+                self.texture_quality.level(),
+            ),
+            Tweak::in_section(SCALABILITY_SECTION, DETAIL_KEY, self.detail_quality.level()),
+            Tweak::in_section(
+                SCALABILITY_SECTION,
+                MATERIAL_KEY,
+                self.material_quality.level(),
+            ),
+            Tweak::in_section(
+                SCALABILITY_SECTION,
+                ANTI_ALIASING_KEY,
+                self.anti_aliasing as u8,
+            ),
+            Tweak::in_section(
+                USER_SETTINGS_SECTION,
+                ANISOTROPIC_KEY,
+                self.anisotropic_filtering,
+            ),
+            Tweak::in_section(USER_SETTINGS_SECTION, BLOOM_KEY, bool_value(self.bloom)),
+            Tweak::in_section(
+                USER_SETTINGS_SECTION,
+                DISTORTION_KEY,
+                bool_value(self.distortion),
+            ),
+        ]
+    }
+}
+
+/// 从 GameUserSettings.ini 读取的画质，缺失的键使用默认值并列在 `missing` 中
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GraphicsReadout {
+    pub settings: GraphicsSettings,
+    pub missing: Vec<String>,
+}
+
+/// 读取 `[节]` 下的键值，不关心节的顺序
+fn parse_ini(content: &str) -> HashMap<(String, String), String> {
+    let mut values = HashMap::new();
+    let mut section = String::new();
+    for line in content.trim_start_matches('\u{feff}').lines() {
+        let line = line.trim();
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.to_string();
+        } else if let Some((key, value)) = line.split_once('=') {
+            values.insert((section.clone(), key.to_string()), value.to_string());
+        }
+    }
+    values
+}
+
+pub fn read_graphics(content: &str) -> Result<GraphicsReadout, GraphicsError> {
+    let values = parse_ini(content);
+    let mut missing = Vec::new();
+    let mut get = |section: &str, key: &str| {
+        let value = values.get(&(section.to_string(), key.to_string())).cloned();
+        if value.is_none() {
+            missing.push(key.to_string());
+        }
+        value
+    };
+    let invalid = |key: &str, value: &str| GraphicsError::InvalidValue {
+        key: key.to_string(),
+        value: value.to_string(),
+    };
+    let quality = |key: &str, value: &str| {
+        value
+            .parse::<u8>()
+            .ok()
+            .and_then(Quality::from_level)
+            .ok_or_else(|| invalid(key, value))
+    };
+    let flag = |key: &str, value: &str| match value.to_ascii_lowercase().as_str() {
+        "true" | "1" => Ok(true),
+        "false" | "0" => Ok(false),
+        _ => Err(invalid(key, value)),
+    };
+
+    let mut settings = GraphicsSettings::default();
+    if let Some(value) = get(SCALABILITY_SECTION, TEXTURE_KEY) {
+        settings.texture_quality = quality(TEXTURE_KEY, &value)?;
+    }
+    if let Some(value) = get(SCALABILITY_SECTION, DETAIL_KEY) {
+        settings.detail_quality = quality(DETAIL_KEY, &value)?;
+    }
+    if let Some(value) = get(SCALABILITY_SECTION, MATERIAL_KEY) {
+        settings.material_quality = quality(MATERIAL_KEY, &value)?;
+    }
+    if let Some(value) = get(SCALABILITY_SECTION, ANTI_ALIASING_KEY) {
+        settings.anti_aliasing = value
+            .parse::<u8>()
+            .ok()
+            .and_then(AntiAliasing::from_level)
+            .ok_or_else(|| invalid(ANTI_ALIASING_KEY, &value))?;
+    }
+    if let Some(value) = get(USER_SETTINGS_SECTION, ANISOTROPIC_KEY) {
+        settings.anisotropic_filtering = value
+            .parse::<u32>()
+            .ok()
+            .filter(|v| ANISOTROPIC_LEVELS.contains(v))
+            .ok_or_else(|| invalid(ANISOTROPIC_KEY, &value))?;
+    }
+    if let Some(value) = get(USER_SETTINGS_SECTION, BLOOM_KEY) {
+        settings.bloom = flag(BLOOM_KEY, &value)?;
+    }
+    if let Some(value) = get(USER_SETTINGS_SECTION, DISTORTION_KEY) {
+        settings.distortion = flag(DISTORTION_KEY, &value)?;
+    }
+    Ok(GraphicsReadout { settings, missing })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GraphicsPreset {
+    pub name: String,
+    pub settings: GraphicsSettings,
+    /// 程序内置的预设，不能被导入覆盖
+    #[serde(
+        default,
+        skip_deserializing,
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub builtin: bool,
+}
+
+fn builtin(name: &str, settings: GraphicsSettings) -> GraphicsPreset {
+    GraphicsPreset {
+        name: name.to_string(),
+        settings,
+        builtin: true,
+    }
+}
+
+/// 内置的竞技预设：低延迟、均衡、画面
+pub fn builtin_presets() -> Vec<GraphicsPreset> {
+    vec![
+        builtin(
+            "LowLatency",
+            GraphicsSettings {
+                texture_quality: Quality::Low,
+                detail_quality: Quality::Low,
+                material_quality: Quality::Low,
+                anti_aliasing: AntiAliasing::None,
+                anisotropic_filtering: 1,
+                bloom: false,
+                distortion: false,
+            },
+        ),
+        builtin(
+            "Balanced",
+            GraphicsSettings {
+                texture_quality: Quality::Medium,
+                detail_quality: Quality::Low,
+                material_quality: Quality::Medium,
+                anti_aliasing: AntiAliasing::Msaa2x,
+                anisotropic_filtering: 4,
+                bloom: false,
+                distortion: false,
+            },
+        ),
+        builtin(
+            "Visual",
+            GraphicsSettings {
+                texture_quality: Quality::High,
+                detail_quality: Quality::High,
+                material_quality: Quality::High,
+                anti_aliasing: AntiAliasing::Msaa4x,
+                anisotropic_filtering: 16,
+                bloom: true,
+                distortion: true,
+            },
+        ),
+    ]
+}
+
+/// 内置预设在前，随后是自定义预设
+pub fn all_presets(custom: &[GraphicsPreset]) -> Vec<GraphicsPreset> {
+    let mut presets = builtin_presets();
+    presets.extend(custom.iter().cloned());
+    presets
+}
+
+/// 加入或替换同名的自定义预设
+pub fn upsert_preset(
+    custom: &mut Vec<GraphicsPreset>,
+    preset: GraphicsPreset,
+) -> Result<(), GraphicsError> {
+    if preset.name.trim().is_empty() {
+        return Err(GraphicsError::EmptyName);
+    }
+    if builtin_presets().iter().any(|p| p.name == preset.name) {
+        return Err(GraphicsError::BuiltinPreset(preset.name));
+    }
+    preset.settings.validate()?;
+    match custom.iter_mut().find(|p| p.name == preset.name) {
+        Some(existing) => *existing = preset,
+        None => custom.push(preset),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::tweak_manager::apply_to_content;
+
+    const SAMPLE: &str = "[/Script/ShooterGame.ShooterGameUserSettings]\r\nbUseVSync=False\r\n\r\n[ScalabilityGroups]\r\nsg.TextureQuality=3\r\nsg.ViewDistanceQuality=2\r\nsg.EffectsQuality=0\r\n";
+
+    #[test]
+    fn read_reports_missing_keys() {
+        let readout = read_graphics(SAMPLE).unwrap();
+        assert_eq!(readout.settings.texture_quality, Quality::Epic);
+        assert_eq!(readout.settings.detail_quality, Quality::High);
+        assert_eq!(
+            readout.missing,
+            vec![
+                MATERIAL_KEY,
+                ANTI_ALIASING_KEY,
+                ANISOTROPIC_KEY,
+                BLOOM_KEY,
+                DISTORTION_KEY
+            ]
+        );
+    }
+
+    #[test]
+    fn applied_presets_read_back() {
+        for preset in builtin_presets() {
+            preset.settings.validate().unwrap();
+            let applied = apply_to_content(SAMPLE, &preset.settings.tweaks());
+            let readout = read_graphics(&applied).unwrap();
+            assert_eq!(readout.settings, preset.settings, "{}", preset.name);
+            assert!(readout.missing.is_empty());
+            assert!(applied.contains("sg.EffectsQuality=0\r\n"));
+        }
+    }
+
+    #[test]
+    fn read_rejects_out_of_range_values() {
+        let content = "[ScalabilityGroups]\nsg.TextureQuality=7\n";
+        assert_eq!(
+            read_graphics(content).unwrap_err(),
+            GraphicsError::InvalidValue {
+                key: TEXTURE_KEY.to_string(),
+                value: "7".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn custom_presets_cannot_shadow_builtins() {
+        let mut custom = Vec::new();
+        let mut preset = GraphicsPreset {
+            name: "Mine".to_string(),
+            settings: GraphicsSettings::default(),
+            builtin: false,
+        };
+        upsert_preset(&mut custom, preset.clone()).unwrap();
+        preset.settings.bloom = false;
+        upsert_preset(&mut custom, preset.clone()).unwrap();
+        assert_eq!(custom, vec![preset.clone()]);
+
+        preset.name = "Balanced".to_string();
+        assert_eq!(
+            upsert_preset(&mut custom, preset.clone()),
+            Err(GraphicsError::BuiltinPreset("Balanced".to_string()))
+        );
+
+        preset.name = "Odd".to_string();
+        preset.settings.anisotropic_filtering = 3;
+        assert_eq!(
+            upsert_preset(&mut custom, preset),
+            Err(GraphicsError::InvalidAnisotropic(3))
+        );
+    }
+}
@@ -0,0 +1,505 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+use time::{OffsetDateTime, UtcOffset};
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+use tracing::{debug, warn};
+
+use crate::{
+    configs::app_config::AppConfig,
+    utils::{
+        constant_manager::HISTORY_FILE, display_manager::DisplayMode, watcher_manager::WatcherEvent,
+    },
+};
+
+/// 一次游戏会话的记录，以 JSON Lines 形式追加到 HISTORY_FILE
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SessionRecord {
+    /// Unix 时间戳（秒）
+    pub start: i64,
+    pub end: i64,
+    pub exe: String,
+    /// 方案名称，没有使用命名方案时为 `WxH@Hz`
+    pub profile: String,
+    pub mode: Option<DisplayMode>,
+    pub apply_succeeded: bool,
+    /// None 表示会话结束时没有执行还原
+    pub restore_succeeded: Option<bool>,
+}
+
+impl SessionRecord {
+    pub fn duration_secs(&self) -> u64 {
+        (self.end - self.start).max(0) as u64
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DailyPlaytime {
+    pub date: String,
+    pub seconds: u64,
+    pub sessions: u32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ProfileSessions {
+    pub profile: String,
+    pub sessions: u32,
+    pub seconds: u64,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FailedSwitches {
+    pub apply_failed: u32,
+    pub restore_failed: u32,
+}
+
+/// 没有命名方案时用分辨率和刷新率作为方案名
+fn mode_label(mode: &DisplayMode) -> String {
+    format!("{}x{}@{}Hz", mode.width, mode.height, mode.refresh_rate)
+}
+
+fn now() -> i64 {
+    OffsetDateTime::now_utc().unix_timestamp()
+}
+
+/// 根据监听器事件拼装会话记录
+#[derive(Default)]
+pub struct SessionRecorder {
+    current: Option<SessionRecord>,
+    ended: bool,
+    /// 当前使用的方案名称，随 ProfileSwitched 更新
+    active_profile: Option<String>,
+}
+
+impl SessionRecorder {
+    pub fn new(active_profile: Option<String>) -> Self {
+        SessionRecorder {
+            active_profile,
+            ..Default::default()
+        }
+    }
+
+    fn profile_label(&self, mode: &DisplayMode) -> String {
+        self.active_profile
+            .clone()
+            .unwrap_or_else(|| mode_label(mode))
+    }
+
+    /// 处理一个事件，会话完整结束时返回该记录
+    pub fn handle(&mut self, event: &WatcherEvent) -> Option<SessionRecord> {
+        match event {
+            WatcherEvent::GameDetected { exe, .. } => {
+                // 登录器在宽限期内重新拉起游戏，或游戏换了进程（如登录器交给
+                // Shipping 进程），都视为同一会话
+                if self.current.is_some() {
+                    self.ended = false;
+                    return None;
+                }
+                self.current = Some(SessionRecord {
+                    start: now(),
+                    end: now(),
+                    exe: exe.clone(),
+                    profile: String::new(),
+                    mode: None,
+                    apply_succeeded: false,
+                    restore_succeeded: None,
+                });
+                self.ended = false;
+                None
+            }
+            WatcherEvent::ModeApplied { mode } => {
+                let profile = self.profile_label(mode);
+                if let Some(record) = self.current.as_mut() {
+                    record.profile = profile;
+                    record.mode = Some(mode.clone());
+                    record.apply_succeeded = true;
+                }
+                None
+            }
+            WatcherEvent::ModeApplyFailed { mode, .. } => {
+                let profile = self.profile_label(mode);
+                if let Some(record) = self.current.as_mut() {
+                    record.profile = profile;
+                    record.mode = Some(mode.clone());
+                    record.apply_succeeded = false;
+                }
+                None
+            }
+            WatcherEvent::GameExited { .. } | WatcherEvent::WatchingStopped { .. } => {
+                // 启动延迟内就退出的进程没有切换过分辨率，不计入历史
+                if self.current.as_ref().is_some_and(|r| r.mode.is_none()) {
+                    self.current = None;
+                    self.ended = false;
+                }
+                if let Some(record) = self.current.as_mut() {
+                    if !self.ended {
+                        record.end = now();
+                        self.ended = true;
+                    }
+                }
+                None
+            }
+            WatcherEvent::ModeRestored => self.finish_with_restore(true),
+            WatcherEvent::ModeRestoreFailed { .. } => self.finish_with_restore(false),
+            WatcherEvent::WatchingStarted { .. } => self.take_finished(),
+            WatcherEvent::ProfileSwitched { name } => {
+                self.active_profile = Some(name.clone());
+                None
+            }
+            WatcherEvent::Armed { .. }
+            | WatcherEvent::Disarmed
+            | WatcherEvent::ModeSuspended
+            | WatcherEvent::ModeConfirmed { .. }
+            | WatcherEvent::ModeReverted { .. }
+            | WatcherEvent::ModeRevertFailed { .. }
+            | WatcherEvent::TweaksReapplied { .. }
+            | WatcherEvent::TweaksReapplyFailed { .. } => None,
+        }
+    }
+
+    fn finish_with_restore(&mut self, succeeded: bool) -> Option<SessionRecord> {
+        if self.ended {
+            if let Some(record) = self.current.as_mut() {
+                record.restore_succeeded = Some(succeeded);
+            }
+        }
+        self.take_finished()
+    }
+
+    fn take_finished(&mut self) -> Option<SessionRecord> {
+        if self.ended {
+            self.ended = false;
+            self.current.take()
+        } else {
+            None
+        }
+    }
+}
+
+pub fn append_session(path: &Path, record: &SessionRecord) -> Result<(), Box<dyn Error>> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)?;
+    Ok(())
+}
+
+pub fn load_sessions(path: &Path) -> Result<Vec<SessionRecord>, Box<dyn Error>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)?;
+    let sessions = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str::<SessionRecord>(line) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                warn!("Skipping malformed session record: {}", e);
+                None
+            }
+        })
+        .collect();
+    Ok(sessions)
+}
+
+/// 按本地日期统计游戏时长，会话计入开始当天
+pub fn daily_playtime(sessions: &[SessionRecord], offset: UtcOffset) -> Vec<DailyPlaytime> {
+    let mut days: BTreeMap<String, (u64, u32)> = BTreeMap::new();
+    for session in sessions {
+        let Ok(start) = OffsetDateTime::from_unix_timestamp(session.start) else {
+            continue;
+        };
+        let date = start.to_offset(offset).date().to_string();
+        let entry = days.entry(date).or_default();
+        entry.0 += session.duration_secs();
+        entry.1 += 1;
+    }
+    days.into_iter()
+        .map(|(date, (seconds, sessions))| DailyPlaytime {
+            date,
+            seconds,
+            sessions,
+        })
+        .collect()
+}
+
+pub fn sessions_per_profile(sessions: &[SessionRecord]) -> Vec<ProfileSessions> {
+    let mut profiles: BTreeMap<String, (u32, u64)> = BTreeMap::new();
+    for session in sessions {
+        let entry = profiles.entry(session.profile.clone()).or_default();
+        entry.0 += 1;
+        entry.1 += session.duration_secs();
+    }
+    profiles
+        .into_iter()
+        .map(|(profile, (sessions, seconds))| ProfileSessions {
+            profile,
+            sessions,
+            seconds,
+        })
+        .collect()
+}
+
+pub fn failed_switches(sessions: &[SessionRecord]) -> FailedSwitches {
+    sessions
+        .iter()
+        .fold(FailedSwitches::default(), |mut acc, s| {
+            if !s.apply_succeeded {
+                acc.apply_failed += 1;
+            }
+            if s.restore_succeeded == Some(false) {
+                acc.restore_failed += 1;
+            }
+            acc
+        })
+}
+
+/// 订阅监听器事件并把结束的会话写入历史文件
+pub async fn record_sessions(mut receiver: Receiver<WatcherEvent>) {
+    let active_profile = AppConfig::load_watcher_config()
+        .ok()
+        .and_then(|c| c.active_profile);
+    let mut recorder = SessionRecorder::new(active_profile);
+    loop {
+        match receiver.recv().await {
+            Ok(event) => {
+                if let Some(record) = recorder.handle(&event) {
+                    debug!("Session finished: {:?}", record);
+                    if let Err(e) = append_session(HISTORY_FILE.as_path(), &record) {
+                        warn!("Failed to record session: {}", e);
+                    }
+                }
+            }
+            Err(RecvError::Lagged(skipped)) => {
+                warn!("Session recorder lagged, {} events skipped", skipped);
+            }
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::offset;
+
+    use super::*;
+
+    fn mode(width: u32, height: u32) -> DisplayMode {
+        DisplayMode {
+            width,
+            height,
+            refresh_rate: 144,
+            bits_per_pixel: 32,
+            monitor_name: String::new(),
+        }
+    }
+
+    fn detected() -> WatcherEvent {
+        WatcherEvent::GameDetected {
+            pid: 1,
+            exe: "VALORANT.exe".to_string(),
+        }
+    }
+
+    fn exited() -> WatcherEvent {
+        WatcherEvent::GameExited { pid: 1 }
+    }
+
+    fn applied() -> WatcherEvent {
+        WatcherEvent::ModeApplied {
+            mode: mode(1440, 1080),
+        }
+    }
+
+    fn record(
+        start: i64,
+        end: i64,
+        profile: &str,
+        apply: bool,
+        restore: Option<bool>,
+    ) -> SessionRecord {
+        SessionRecord {
+            start,
+            end,
+            exe: "VALORANT.exe".to_string(),
+            profile: profile.to_string(),
+            mode: Some(mode(1440, 1080)),
+            apply_succeeded: apply,
+            restore_succeeded: restore,
+        }
+    }
+
+    #[test]
+    fn session_is_recorded_with_active_profile_name() {
+        let mut recorder = SessionRecorder::new(Some("4:3".to_string()));
+        assert!(recorder.handle(&detected()).is_none());
+        assert!(recorder.handle(&applied()).is_none());
+        assert!(recorder.handle(&exited()).is_none());
+        let record = recorder.handle(&WatcherEvent::ModeRestored).unwrap();
+        assert_eq!(record.profile, "4:3");
+        assert!(record.apply_succeeded);
+        assert_eq!(record.restore_succeeded, Some(true));
+        assert!(record.end >= record.start);
+    }
+
+    #[test]
+    fn profile_follows_switches_and_falls_back_to_mode() {
+        let mut recorder = SessionRecorder::default();
+        recorder.handle(&detected());
+        recorder.handle(&applied());
+        recorder.handle(&exited());
+        let record = recorder.handle(&WatcherEvent::ModeRestored).unwrap();
+        assert_eq!(record.profile, "1440x1080@144Hz");
+
+        recorder.handle(&WatcherEvent::ProfileSwitched {
+            name: "16:9".to_string(),
+        });
+        recorder.handle(&detected());
+        recorder.handle(&applied());
+        recorder.handle(&exited());
+        let record = recorder.handle(&WatcherEvent::ModeRestored).unwrap();
+        assert_eq!(record.profile, "16:9");
+    }
+
+    #[test]
+    fn exit_before_switch_is_not_recorded() {
+        let mut recorder = SessionRecorder::default();
+        recorder.handle(&detected());
+        recorder.handle(&exited());
+        assert!(recorder.handle(&WatcherEvent::ModeRestored).is_none());
+        assert!(recorder
+            .handle(&WatcherEvent::WatchingStarted {
+                process_path: String::new()
+            })
+            .is_none());
+    }
+
+    #[test]
+    fn relaunch_within_grace_continues_session() {
+        let mut recorder = SessionRecorder::default();
+        recorder.handle(&detected());
+        recorder.handle(&applied());
+        recorder.handle(&exited());
+        // 登录器在还原前重新拉起游戏
+        assert!(recorder.handle(&detected()).is_none());
+        recorder.handle(&exited());
+        let record = recorder.handle(&WatcherEvent::ModeRestoreFailed {
+            error: "failed".to_string(),
+        });
+        assert_eq!(record.unwrap().restore_succeeded, Some(false));
+        assert!(recorder.handle(&WatcherEvent::ModeRestored).is_none());
+    }
+
+    #[test]
+    fn pid_change_without_exit_continues_session() {
+        let mut recorder = SessionRecorder::default();
+        recorder.handle(&detected());
+        recorder.handle(&applied());
+        // 监听器直接报告了新的 PID，中间没有 GameExited
+        assert!(recorder
+            .handle(&WatcherEvent::GameDetected {
+                pid: 2,
+                exe: "VALORANT-Win64-Shipping.exe".to_string(),
+            })
+            .is_none());
+        recorder.handle(&WatcherEvent::GameExited { pid: 2 });
+        let record = recorder.handle(&WatcherEvent::ModeRestored).unwrap();
+        assert_eq!(record.exe, "VALORANT.exe");
+        assert!(record.apply_succeeded);
+        assert_eq!(record.restore_succeeded, Some(true));
+    }
+
+    #[test]
+    fn failed_apply_is_recorded() {
+        let mut recorder = SessionRecorder::default();
+        recorder.handle(&detected());
+        recorder.handle(&WatcherEvent::ModeApplyFailed {
+            mode: mode(1280, 960),
+            error: "bad mode".to_string(),
+        });
+        recorder.handle(&WatcherEvent::WatchingStopped {
+            process_path: String::new(),
+        });
+        // 停止监听后没有还原事件，下次开始监听时结束该会话
+        let record = recorder
+            .handle(&WatcherEvent::WatchingStarted {
+                process_path: String::new(),
+            })
+            .unwrap();
+        assert!(!record.apply_succeeded);
+        assert_eq!(record.restore_succeeded, None);
+        assert_eq!(record.profile, "1280x960@144Hz");
+    }
+
+    #[test]
+    fn daily_playtime_uses_local_date_of_start() {
+        // 2024-01-01 23:30 UTC，东八区为 1 月 2 日
+        let late = 1_704_151_800;
+        let sessions = [
+            record(late, late + 3600, "a", true, Some(true)),
+            record(late + 86_400, late + 86_400 + 600, "a", true, Some(true)),
+            record(
+                late + 86_400 + 1000,
+                late + 86_400 + 1600,
+                "b",
+                true,
+                Some(true),
+            ),
+        ];
+        let utc = daily_playtime(&sessions, UtcOffset::UTC);
+        assert_eq!(
+            utc.iter()
+                .map(|d| (d.date.as_str(), d.seconds, d.sessions))
+                .collect::<Vec<_>>(),
+            [("2024-01-01", 3600, 1), ("2024-01-02", 1200, 2)]
+        );
+        let local = daily_playtime(&sessions, offset!(+8));
+        assert_eq!(
+            local
+                .iter()
+                .map(|d| (d.date.as_str(), d.seconds, d.sessions))
+                .collect::<Vec<_>>(),
+            [("2024-01-02", 3600, 1), ("2024-01-03", 1200, 2)]
+        );
+    }
+
+    #[test]
+    fn sessions_are_grouped_by_profile() {
+        let sessions = [
+            record(0, 100, "4:3", true, Some(true)),
+            record(200, 500, "16:9", true, Some(true)),
+            record(600, 650, "4:3", true, Some(true)),
+            // 结束时间早于开始时间按 0 计
+            record(700, 600, "4:3", true, Some(true)),
+        ];
+        assert_eq!(
+            sessions_per_profile(&sessions)
+                .iter()
+                .map(|p| (p.profile.as_str(), p.sessions, p.seconds))
+                .collect::<Vec<_>>(),
+            [("16:9", 1, 300), ("4:3", 3, 150)]
+        );
+    }
+
+    #[test]
+    fn failed_switches_count_apply_and_restore_failures() {
+        let sessions = [
+            record(0, 1, "a", false, None),
+            record(0, 1, "a", true, Some(false)),
+            record(0, 1, "a", false, Some(false)),
+            record(0, 1, "a", true, Some(true)),
+            record(0, 1, "a", true, None),
+        ];
+        let failed = failed_switches(&sessions);
+        assert_eq!((failed.apply_failed, failed.restore_failed), (2, 2));
+    }
+}
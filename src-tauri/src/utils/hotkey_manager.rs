@@ -0,0 +1,271 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::configs::app_config::ProfileConfig;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum HotkeyError {
+    #[error("Empty hotkey")]
+    Empty,
+    #[error("Unknown modifier: {0}")]
+    UnknownModifier(String),
+    #[error("Hotkey has no key: {0}")]
+    MissingKey(String),
+    #[error("Hotkey needs at least one modifier: {0}")]
+    MissingModifier(String),
+    #[error("Hotkey {0} is bound to more than one action")]
+    Duplicate(String),
+}
+
+/// 快捷键可触发的动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HotkeyAction {
+    /// 在拉伸分辨率和桌面分辨率之间切换
+    ToggleStretched,
+    /// 切换到下一个分辨率方案
+    CycleProfile,
+    /// 暂停或恢复监听
+    PauseWatcher,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct HotkeyConfig {
+    pub action: HotkeyAction,
+    /// 如 `Ctrl+Alt+F9`，修饰键顺序和大小写不限
+    pub keys: String,
+}
+
+/// 规范化后的组合键，修饰键顺序和大小写不影响比较
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Accelerator {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub super_key: bool,
+    /// 大写的主键名，如 `F9`、`A`
+    pub key: String,
+}
+
+impl FromStr for Accelerator {
+    type Err = HotkeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s
+            .split('+')
+            .map(|p| p.trim())
+            .filter(|p| !p.is_empty())
+            .collect::<Vec<_>>();
+        let Some((key, modifiers)) = parts.split_last() else {
+            return Err(HotkeyError::Empty);
+        };
+
+        let mut accelerator = Accelerator {
+            ctrl: false,
+            alt: false,
+            shift: false,
+            super_key: false,
+            key: key.to_uppercase(),
+        };
+        if is_modifier(key) {
+            return Err(HotkeyError::MissingKey(s.to_string()));
+        }
+        for modifier in modifiers {
+            match modifier.to_uppercase().as_str() {
+                "CTRL" | "CONTROL" => accelerator.ctrl = true,
+                "ALT" | "OPTION" => accelerator.alt = true,
+                "SHIFT" => accelerator.shift = true,
+                "SUPER" | "WIN" | "META" | "CMD" => accelerator.super_key = true,
+                _ => return Err(HotkeyError::UnknownModifier(modifier.to_string())),
+            }
+        }
+        // 没有修饰键的全局快捷键会吞掉游戏里的按键
+        if !(accelerator.ctrl || accelerator.alt || accelerator.shift || accelerator.super_key) {
+            return Err(HotkeyError::MissingModifier(s.to_string()));
+        }
+        Ok(accelerator)
+    }
+}
+
+fn is_modifier(key: &str) -> bool {
+    matches!(
+        key.to_uppercase().as_str(),
+        "CTRL" | "CONTROL" | "ALT" | "OPTION" | "SHIFT" | "SUPER" | "WIN" | "META" | "CMD"
+    )
+}
+
+impl fmt::Display for Accelerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.super_key {
+            write!(f, "Super+")?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+/// 组合键到动作的映射，不涉及系统注册
+#[derive(Debug, Default, Clone)]
+pub struct HotkeyMap {
+    bindings: HashMap<Accelerator, HotkeyAction>,
+}
+
+impl HotkeyMap {
+    /// 解析快捷键配置，无法解析或与前面重复的条目跳过，连同原因一起返回
+    pub fn from_config(hotkeys: &[HotkeyConfig]) -> (Self, Vec<HotkeyError>) {
+        let mut bindings = HashMap::new();
+        let mut errors = Vec::new();
+        for hotkey in hotkeys {
+            let accelerator = match hotkey.keys.parse::<Accelerator>() {
+                Ok(accelerator) => accelerator,
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
+            };
+            if bindings.contains_key(&accelerator) {
+                errors.push(HotkeyError::Duplicate(accelerator.to_string()));
+                continue;
+            }
+            bindings.insert(accelerator, hotkey.action);
+        }
+        (HotkeyMap { bindings }, errors)
+    }
+
+    pub fn bindings(&self) -> impl Iterator<Item = (&Accelerator, HotkeyAction)> {
+        self.bindings.iter().map(|(a, action)| (a, *action))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StretchCommand {
+    Apply,
+    Restore,
+}
+
+/// 当前已是拉伸分辨率时还原，否则切换
+pub fn toggle_command(current: (u32, u32), stretched: (u32, u32)) -> StretchCommand {
+    if current == stretched {
+        StretchCommand::Restore
+    } else {
+        StretchCommand::Apply
+    }
+}
+
+/// 当前方案的下一个方案，末尾回到第一个；当前方案不存在时从第一个开始
+pub fn next_profile<'a>(
+    profiles: &'a [ProfileConfig],
+    active: Option<&str>,
+) -> Option<&'a ProfileConfig> {
+    let index = active
+        .and_then(|name| profiles.iter().position(|p| p.name == name))
+        .map(|i| (i + 1) % profiles.len())
+        .unwrap_or(0);
+    profiles.get(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str) -> ProfileConfig {
+        ProfileConfig {
+            name: name.to_string(),
+            width: 1440,
+            height: 1080,
+            fps: 144,
+            monitors: None,
+        }
+    }
+
+    fn hotkey(action: HotkeyAction, keys: &str) -> HotkeyConfig {
+        HotkeyConfig {
+            action,
+            keys: keys.to_string(),
+        }
+    }
+
+    #[test]
+    fn accelerator_ignores_order_and_case() {
+        let a = "Ctrl+Alt+F9".parse::<Accelerator>().unwrap();
+        let b = "alt + control + f9".parse::<Accelerator>().unwrap();
+        assert_eq!(a, b);
+        assert_eq!(b.to_string(), "Ctrl+Alt+F9");
+    }
+
+    #[test]
+    fn accelerator_rejects_invalid_input() {
+        assert_eq!("".parse::<Accelerator>(), Err(HotkeyError::Empty));
+        assert_eq!(
+            "Ctrl+Alt".parse::<Accelerator>(),
+            Err(HotkeyError::MissingKey("Ctrl+Alt".to_string()))
+        );
+        assert_eq!(
+            "Hyper+F9".parse::<Accelerator>(),
+            Err(HotkeyError::UnknownModifier("Hyper".to_string()))
+        );
+        assert_eq!(
+            "F9".parse::<Accelerator>(),
+            Err(HotkeyError::MissingModifier("F9".to_string()))
+        );
+    }
+
+    #[test]
+    fn map_skips_duplicate_bindings() {
+        let (map, errors) = HotkeyMap::from_config(&[
+            hotkey(HotkeyAction::ToggleStretched, "Ctrl+Alt+F9"),
+            hotkey(HotkeyAction::CycleProfile, "alt+ctrl+f9"),
+        ]);
+        assert_eq!(errors, [HotkeyError::Duplicate("Ctrl+Alt+F9".to_string())]);
+        // 保留先出现的绑定
+        assert_eq!(
+            map.bindings().collect::<Vec<_>>(),
+            [(
+                &"Ctrl+Alt+F9".parse::<Accelerator>().unwrap(),
+                HotkeyAction::ToggleStretched
+            )]
+        );
+    }
+
+    #[test]
+    fn map_skips_invalid_entries() {
+        let (map, errors) = HotkeyMap::from_config(&[
+            hotkey(HotkeyAction::ToggleStretched, "F9"),
+            hotkey(HotkeyAction::PauseWatcher, "Ctrl+Alt+F11"),
+        ]);
+        assert_eq!(errors, [HotkeyError::MissingModifier("F9".to_string())]);
+        assert_eq!(map.bindings().count(), 1);
+    }
+
+    #[test]
+    fn toggle_restores_when_already_stretched() {
+        assert_eq!(
+            toggle_command((1440, 1080), (1440, 1080)),
+            StretchCommand::Restore
+        );
+        assert_eq!(
+            toggle_command((2560, 1440), (1440, 1080)),
+            StretchCommand::Apply
+        );
+    }
+
+    #[test]
+    fn cycle_wraps_around_profiles() {
+        let profiles = vec![profile("a"), profile("b"), profile("c")];
+        assert_eq!(next_profile(&profiles, Some("a")).unwrap().name, "b");
+        assert_eq!(next_profile(&profiles, Some("c")).unwrap().name, "a");
+        assert_eq!(next_profile(&profiles, Some("missing")).unwrap().name, "a");
+        assert_eq!(next_profile(&profiles, None).unwrap().name, "a");
+        assert!(next_profile(&[], Some("a")).is_none());
+    }
+}
@@ -0,0 +1,152 @@
+use std::{
+    path::Path,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use sysinfo::System;
+use thiserror::Error;
+use tokio::time::{sleep, Duration};
+use tracing::{debug, info};
+
+use crate::utils::{
+    command_manager::spawn_detached, matcher_manager::ProcessMatcher,
+    watcher_manager::matching_processes,
+};
+
+/// 启动失败的原因，序列化后交给前端区分处理
+#[derive(Error, Debug, Serialize)]
+#[serde(tag = "Kind", content = "Detail")]
+pub enum LaunchError {
+    #[error("Failed to load config: {0}")]
+    ConfigFailed(String),
+    #[error("Launcher path not set")]
+    LauncherNotSet,
+    #[error("Launcher not found: {0}")]
+    LauncherNotFound(String),
+    #[error("Failed to modify cfg files: {0}")]
+    CfgFailed(String),
+    #[error("Failed to start watching: {0}")]
+    WatcherFailed(String),
+    #[error("Failed to start launcher: {0}")]
+    SpawnFailed(String),
+    #[error("Game process did not appear within {0} ms")]
+    Timeout(u64),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LaunchOptions {
+    /// 传给登录器的参数
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// 启动前修改游戏 cfg 文件
+    #[serde(default)]
+    pub apply_cfg: bool,
+    /// 启动前开始监听
+    #[serde(default)]
+    pub arm_watcher: bool,
+    /// 等待游戏进程出现的时间（毫秒）
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_timeout_ms() -> u64 {
+    120_000
+}
+
+impl Default for LaunchOptions {
+    fn default() -> Self {
+        LaunchOptions {
+            args: Vec::new(),
+            apply_cfg: false,
+            arm_watcher: false,
+            timeout_ms: default_timeout_ms(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LaunchReport {
+    pub pid: u32,
+    pub exe: String,
+    /// 从启动登录器到检测到游戏进程的耗时
+    pub elapsed_ms: u64,
+}
+
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 直接启动登录器，不等待其退出
+pub fn spawn_launcher(launcher_path: &str, args: &[String]) -> Result<(), LaunchError> {
+    let launcher = Path::new(launcher_path);
+    if !launcher.exists() {
+        return Err(LaunchError::LauncherNotFound(launcher_path.to_string()));
+    }
+    let args = args.iter().map(|a| a.as_str()).collect::<Vec<_>>();
+    spawn_detached(launcher, &args).map_err(|e| LaunchError::SpawnFailed(e.to_string()))?;
+    info!("Launcher started: {} {:?}", launcher_path, args);
+    Ok(())
+}
+
+/// 在匹配的进程中找出启动登录器之后才启动的一个，之前遗留的进程不算
+fn started_since(processes: Vec<(u32, String, u64)>, launched_at: u64) -> Option<(u32, String)> {
+    processes
+        .into_iter()
+        .find(|(_, _, start_time)| *start_time >= launched_at)
+        .map(|(pid, exe, _)| (pid, exe))
+}
+
+/// 轮询直到出现匹配的进程，超时返回 `LaunchError::Timeout`
+///
+/// 启动前已在运行的进程不算，只接受在 `started_at` 之后启动的进程。
+pub async fn wait_for_process(
+    matcher: &ProcessMatcher,
+    started_at: Instant,
+    timeout: Duration,
+) -> Result<LaunchReport, LaunchError> {
+    // 进程启动时间只精确到秒，向下取整避免漏掉同一秒内启动的进程
+    let launched_at = (SystemTime::now() - started_at.elapsed())
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let mut system = System::new();
+    loop {
+        let found = started_since(matching_processes(&mut system, matcher), launched_at);
+        if let Some((pid, exe)) = found {
+            let elapsed_ms = started_at.elapsed().as_millis() as u64;
+            info!(
+                "Game process {} ({}) ready after {} ms",
+                exe, pid, elapsed_ms
+            );
+            return Ok(LaunchReport {
+                pid,
+                exe,
+                elapsed_ms,
+            });
+        }
+        if started_at.elapsed() >= timeout {
+            return Err(LaunchError::Timeout(timeout.as_millis() as u64));
+        }
+        debug!("Waiting for game process");
+        sleep(WAIT_POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lingering_process_does_not_hide_new_one() {
+        let processes = vec![
+            (10, "VALORANT.exe".to_string(), 1_000),
+            (20, "VALORANT-Win64-Shipping.exe".to_string(), 2_000),
+        ];
+        assert_eq!(
+            started_since(processes.clone(), 2_000),
+            Some((20, "VALORANT-Win64-Shipping.exe".to_string()))
+        );
+        assert_eq!(started_since(processes, 2_001), None);
+    }
+}
@@ -0,0 +1,488 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::utils::display_manager::{
+    apply_monitor_placements, read_monitor_placements, DisplayError, DisplayMode, MonitorPlacement,
+};
+
+#[derive(Error, Debug)]
+pub enum LayoutError {
+    #[error("Monitor not found: {0}")]
+    MonitorNotFound(String),
+    #[error(transparent)]
+    Display(#[from] DisplayError),
+    #[error("Failed to save monitor layout: {0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid monitor layout file: {0}")]
+    InvalidSnapshot(#[from] serde_json::Error),
+}
+
+/// 游戏会话期间其他显示器的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OtherMonitors {
+    /// 保持启用
+    #[default]
+    Keep,
+    /// 从桌面分离，退出游戏后重新启用
+    Disable,
+}
+
+/// 方案的多显示器设置，只在游戏会话期间生效
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MonitorLayout {
+    /// 游戏所在的显示器，为空时使用切换分辨率的显示器，再为空时使用主显示器
+    #[serde(rename = "Monitor", default)]
+    pub monitor: String,
+    /// 临时设为主显示器，游戏会在主显示器上打开
+    #[serde(rename = "MakePrimary", default)]
+    pub make_primary: bool,
+    #[serde(rename = "Others", default)]
+    pub others: OtherMonitors,
+    /// 目标显示器分辨率变化后，保持其他显示器贴在原来的边上
+    #[serde(rename = "KeepPositions", default)]
+    pub keep_positions: bool,
+}
+
+/// 游戏所在的显示器：优先使用设置中的显示器，其次是切换分辨率的显示器，最后是主显示器
+fn target_monitor<'a>(
+    current: &'a [MonitorPlacement],
+    layout: &MonitorLayout,
+    mode: &DisplayMode,
+) -> Result<&'a MonitorPlacement, LayoutError> {
+    let name = if layout.monitor.is_empty() {
+        &mode.monitor_name
+    } else {
+        &layout.monitor
+    };
+    current
+        .iter()
+        .filter(|m| m.active)
+        .find(|m| {
+            if name.is_empty() {
+                m.primary
+            } else {
+                &m.mode.monitor_name == name
+            }
+        })
+        .ok_or_else(|| LayoutError::MonitorNotFound(name.clone()))
+}
+
+/// 计算游戏期间的布局，`current` 为切换前的布局
+pub fn plan_layout(
+    current: &[MonitorPlacement],
+    layout: &MonitorLayout,
+    mode: &DisplayMode,
+) -> Result<Vec<MonitorPlacement>, LayoutError> {
+    let target = target_monitor(current, layout, mode)?;
+    let right = target.x + target.mode.width as i32;
+    let bottom = target.y + target.mode.height as i32;
+    let dx = mode.width as i32 - target.mode.width as i32;
+    let dy = mode.height as i32 - target.mode.height as i32;
+    // 主显示器必须位于桌面原点
+    let (origin_x, origin_y) = if layout.make_primary {
+        (target.x, target.y)
+    } else {
+        (0, 0)
+    };
+
+    let planned = current
+        .iter()
+        .map(|monitor| {
+            let mut planned = monitor.clone();
+            if monitor.mode.monitor_name == target.mode.monitor_name {
+                planned.mode.width = mode.width;
+                planned.mode.height = mode.height;
+                planned.mode.refresh_rate = mode.refresh_rate;
+                planned.primary |= layout.make_primary;
+            } else {
+                planned.primary &= !layout.make_primary;
+                planned.active &= layout.others == OtherMonitors::Keep;
+                // 位于目标显示器右侧或下方的显示器随分辨率变化平移
+                if layout.keep_positions {
+                    if monitor.x >= right {
+                        planned.x += dx;
+                    }
+                    if monitor.y >= bottom {
+                        planned.y += dy;
+                    }
+                }
+            }
+            planned.x -= origin_x;
+            planned.y -= origin_y;
+            planned
+        })
+        .collect();
+    Ok(planned)
+}
+
+/// 显示器布局读写抽象，便于在没有多显示器的环境下测试
+pub trait LayoutBackend: Send + Sync {
+    fn read(&self) -> Result<Vec<MonitorPlacement>, DisplayError>;
+    fn apply(&self, placements: &[MonitorPlacement]) -> Result<(), DisplayError>;
+}
+
+pub struct Win32LayoutBackend;
+
+impl LayoutBackend for Win32LayoutBackend {
+    fn read(&self) -> Result<Vec<MonitorPlacement>, DisplayError> {
+        read_monitor_placements()
+    }
+
+    fn apply(&self, placements: &[MonitorPlacement]) -> Result<(), DisplayError> {
+        apply_monitor_placements(placements)
+    }
+}
+
+/// 修改布局前记录原始布局，并写入快照文件，异常退出后下次启动时据此还原
+///
+/// 监听任务和命令都会调用，内部加锁，方法只需要 `&self`。
+///
+/// 写入布局期间一直持有锁，保证 engage 和 release 不会交错，保存的原始布局始终与屏幕一致。
+/// 写入是同步的 Win32 调用，在异步任务中调用时应放到阻塞线程上执行。
+pub struct LayoutController {
+    backend: Box<dyn LayoutBackend>,
+    snapshot_path: PathBuf,
+    saved: Mutex<Option<Vec<MonitorPlacement>>>,
+}
+
+impl LayoutController {
+    pub fn new(backend: Box<dyn LayoutBackend>, snapshot_path: PathBuf) -> Self {
+        LayoutController {
+            backend,
+            snapshot_path,
+            saved: Mutex::new(None),
+        }
+    }
+
+    /// 按设置调整布局并把目标分辨率应用到目标显示器，返回目标显示器名称
+    ///
+    /// 重复调用时始终以第一次保存的原始布局为基准。
+    pub fn engage(
+        &self,
+        layout: &MonitorLayout,
+        mode: &DisplayMode,
+    ) -> Result<String, LayoutError> {
+        let mut saved = self.saved.lock().unwrap();
+        let original = match saved.as_ref() {
+            Some(original) => original.clone(),
+            None => {
+                let original = self.backend.read()?;
+                write_snapshot(&self.snapshot_path, &original)?;
+                original
+            }
+        };
+        let planned = match plan_layout(&original, layout, mode) {
+            Ok(planned) => planned,
+            Err(e) => {
+                if saved.is_none() {
+                    remove_snapshot(&self.snapshot_path)?;
+                }
+                return Err(e);
+            }
+        };
+        let target = target_monitor(&original, layout, mode)?
+            .mode
+            .monitor_name
+            .clone();
+        if let Err(e) = self.backend.apply(&planned) {
+            // 部分显示器可能已经写入，按原始布局回滚
+            if let Err(revert) = self.backend.apply(&original) {
+                warn!("Failed to roll back monitor layout: {}", revert);
+                *saved = Some(original);
+            } else {
+                *saved = None;
+                remove_snapshot(&self.snapshot_path)?;
+            }
+            return Err(e.into());
+        }
+        info!("Monitor layout changed: {:?}", planned);
+        *saved = Some(original);
+        Ok(target)
+    }
+
+    /// 还原为修改前的布局
+    pub fn release(&self) -> Result<(), LayoutError> {
+        let mut saved = self.saved.lock().unwrap();
+        let Some(original) = saved.as_ref() else {
+            return Ok(());
+        };
+        self.backend.apply(original)?;
+        info!("Monitor layout restored to {:?}", original);
+        *saved = None;
+        remove_snapshot(&self.snapshot_path)
+    }
+
+    /// 先还原布局，再执行按注册表还原分辨率的操作
+    ///
+    /// 游戏期间的布局写入了注册表，直接按注册表还原会把游戏布局重新应用一遍。
+    pub fn release_then<T>(
+        &self,
+        restore: impl FnOnce() -> Result<T, DisplayError>,
+    ) -> Result<T, DisplayError> {
+        if let Err(e) = self.release() {
+            warn!("Failed to restore monitor layout: {}", e);
+        }
+        restore()
+    }
+
+    /// 上次退出时没有还原布局，按快照文件还原，返回是否执行了还原
+    pub fn recover(&self) -> Result<bool, LayoutError> {
+        if !self.snapshot_path.exists() {
+            return Ok(false);
+        }
+        let content = fs::read_to_string(&self.snapshot_path)?;
+        match serde_json::from_str::<Vec<MonitorPlacement>>(&content) {
+            Ok(original) => {
+                warn!("Recovering monitor layout from last run: {:?}", original);
+                *self.saved.lock().unwrap() = Some(original);
+                self.release()?;
+                Ok(true)
+            }
+            Err(e) => {
+                remove_snapshot(&self.snapshot_path)?;
+                Err(e.into())
+            }
+        }
+    }
+}
+
+fn write_snapshot(path: &Path, placements: &[MonitorPlacement]) -> Result<(), LayoutError> {
+    fs::write(path, serde_json::to_string(placements)?)?;
+    Ok(())
+}
+
+fn remove_snapshot(path: &Path) -> Result<(), LayoutError> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::utils::test_util::TempDir;
+
+    fn monitor(
+        name: &str,
+        width: u32,
+        height: u32,
+        x: i32,
+        y: i32,
+        primary: bool,
+    ) -> MonitorPlacement {
+        MonitorPlacement {
+            mode: DisplayMode {
+                width,
+                height,
+                refresh_rate: 144,
+                bits_per_pixel: 32,
+                monitor_name: name.to_string(),
+            },
+            x,
+            y,
+            primary,
+            active: true,
+        }
+    }
+
+    fn game_mode(monitor_name: &str) -> DisplayMode {
+        DisplayMode {
+            width: 1440,
+            height: 1080,
+            refresh_rate: 240,
+            bits_per_pixel: 32,
+            monitor_name: monitor_name.to_string(),
+        }
+    }
+
+    /// 主显示器 2560x1440，副显示器在右侧，顶部对齐
+    fn desk() -> Vec<MonitorPlacement> {
+        vec![
+            monitor("DISPLAY1", 2560, 1440, 0, 0, true),
+            monitor("DISPLAY2", 1920, 1080, 2560, 0, false),
+        ]
+    }
+
+    #[test]
+    fn keeps_secondary_attached_to_shrunk_primary() {
+        let layout = MonitorLayout {
+            keep_positions: true,
+            ..Default::default()
+        };
+        let planned = plan_layout(&desk(), &layout, &game_mode("")).unwrap();
+        assert_eq!(planned[0].mode.width, 1440);
+        assert_eq!(planned[0].mode.refresh_rate, 240);
+        assert!(planned[0].primary);
+        assert_eq!((planned[1].x, planned[1].y), (1440, 0));
+        assert_eq!(planned[1].mode, desk()[1].mode);
+    }
+
+    #[test]
+    fn without_keep_positions_others_are_untouched() {
+        let planned = plan_layout(&desk(), &MonitorLayout::default(), &game_mode("")).unwrap();
+        assert_eq!(planned[1], desk()[1]);
+    }
+
+    #[test]
+    fn make_primary_moves_origin_to_target() {
+        let layout = MonitorLayout {
+            monitor: "DISPLAY2".to_string(),
+            make_primary: true,
+            keep_positions: true,
+            ..Default::default()
+        };
+        let planned = plan_layout(&desk(), &layout, &game_mode("")).unwrap();
+        assert!(!planned[0].primary);
+        assert_eq!((planned[0].x, planned[0].y), (-2560, 0));
+        assert!(planned[1].primary);
+        assert_eq!((planned[1].x, planned[1].y), (0, 0));
+        assert_eq!(planned[1].mode.width, 1440);
+        // 原主显示器在目标左侧，不受分辨率变化影响
+        assert_eq!(planned[0].mode, desk()[0].mode);
+    }
+
+    #[test]
+    fn disable_others_detaches_them() {
+        let layout = MonitorLayout {
+            others: OtherMonitors::Disable,
+            ..Default::default()
+        };
+        let planned = plan_layout(&desk(), &layout, &game_mode("DISPLAY1")).unwrap();
+        assert!(planned[0].active);
+        assert!(!planned[1].active);
+    }
+
+    #[test]
+    fn unknown_monitor_is_rejected() {
+        let layout = MonitorLayout {
+            monitor: "DISPLAY9".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            plan_layout(&desk(), &layout, &game_mode("")),
+            Err(LayoutError::MonitorNotFound(name)) if name == "DISPLAY9"
+        ));
+    }
+
+    #[derive(Clone)]
+    struct FakeBackend {
+        layout: Arc<Mutex<Vec<MonitorPlacement>>>,
+    }
+
+    impl FakeBackend {
+        fn new() -> Self {
+            FakeBackend {
+                layout: Arc::new(Mutex::new(desk())),
+            }
+        }
+
+        fn current(&self) -> Vec<MonitorPlacement> {
+            self.layout.lock().unwrap().clone()
+        }
+    }
+
+    impl LayoutBackend for FakeBackend {
+        fn read(&self) -> Result<Vec<MonitorPlacement>, DisplayError> {
+            Ok(self.current())
+        }
+
+        fn apply(&self, placements: &[MonitorPlacement]) -> Result<(), DisplayError> {
+            *self.layout.lock().unwrap() = placements.to_vec();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn engage_then_release_restores_original() {
+        let backend = FakeBackend::new();
+        let dir = TempDir::new("layout_engage");
+        let path = dir.join("layout.json");
+        let controller = LayoutController::new(Box::new(backend.clone()), path.clone());
+        let layout = MonitorLayout {
+            monitor: "DISPLAY2".to_string(),
+            make_primary: true,
+            ..Default::default()
+        };
+
+        let target = controller.engage(&layout, &game_mode("")).unwrap();
+        assert_eq!(target, "DISPLAY2");
+        assert!(backend.current()[1].primary);
+        assert!(path.exists());
+
+        // 切回游戏时再次调整，仍以原始布局为基准
+        controller.engage(&layout, &game_mode("")).unwrap();
+        controller.release().unwrap();
+        assert_eq!(backend.current(), desk());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn registry_restore_runs_after_layout_release() {
+        let backend = FakeBackend::new();
+        let dir = TempDir::new("layout_registry");
+        let path = dir.join("layout.json");
+        let controller = LayoutController::new(Box::new(backend.clone()), path.clone());
+        let layout = MonitorLayout {
+            others: OtherMonitors::Disable,
+            ..Default::default()
+        };
+        controller.engage(&layout, &game_mode("")).unwrap();
+
+        // 按注册表还原时，注册表中必须已经是原始布局
+        let seen = controller.release_then(|| Ok(backend.current())).unwrap();
+        assert_eq!(seen, desk());
+        assert!(!path.exists());
+
+        // 没有修改布局时直接执行还原
+        let result = controller.release_then(|| Err::<(), _>(DisplayError::Unsupported));
+        assert!(matches!(result, Err(DisplayError::Unsupported)));
+        assert_eq!(backend.current(), desk());
+    }
+
+    #[test]
+    fn failed_plan_leaves_no_snapshot() {
+        let backend = FakeBackend::new();
+        let dir = TempDir::new("layout_failed");
+        let path = dir.join("layout.json");
+        let controller = LayoutController::new(Box::new(backend.clone()), path.clone());
+        let layout = MonitorLayout {
+            monitor: "DISPLAY9".to_string(),
+            ..Default::default()
+        };
+        assert!(controller.engage(&layout, &game_mode("")).is_err());
+        assert!(!path.exists());
+        assert_eq!(backend.current(), desk());
+    }
+
+    #[test]
+    fn recover_restores_layout_left_by_crash() {
+        let backend = FakeBackend::new();
+        let dir = TempDir::new("layout_crash");
+        let path = dir.join("layout.json");
+        let crashed = LayoutController::new(Box::new(backend.clone()), path.clone());
+        let layout = MonitorLayout {
+            others: OtherMonitors::Disable,
+            ..Default::default()
+        };
+        crashed.engage(&layout, &game_mode("")).unwrap();
+        // 模拟进程崩溃：控制器被丢弃，没有调用 release
+        drop(crashed);
+        assert!(!backend.current()[1].active);
+
+        let controller = LayoutController::new(Box::new(backend.clone()), path.clone());
+        assert!(controller.recover().unwrap());
+        assert_eq!(backend.current(), desk());
+        assert!(!path.exists());
+        assert!(!controller.recover().unwrap());
+    }
+}
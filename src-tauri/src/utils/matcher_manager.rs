@@ -0,0 +1,345 @@
+use std::{ffi::OsString, fs, path::Path};
+
+use glob::{MatchOptions, Pattern};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MatcherError {
+    #[error("Invalid glob pattern: {0}")]
+    InvalidGlob(#[from] glob::PatternError),
+    #[error("Invalid regex: {0}")]
+    InvalidRegex(#[from] regex::Error),
+}
+
+/// 进程匹配规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "Kind", content = "Pattern")]
+pub enum MatchRule {
+    /// 可执行文件名，不区分大小写
+    Name(String),
+    /// 规范化后的完整路径
+    Path(String),
+    /// 路径通配符，如 `**/VALORANT/live/*.exe`
+    Glob(String),
+    /// 路径正则表达式
+    Regex(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MatcherConfig {
+    pub rule: MatchRule,
+    /// 命令行中必须包含的参数（子串匹配，不区分大小写）
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+impl MatcherConfig {
+    pub fn path(path: &str) -> Self {
+        MatcherConfig {
+            rule: MatchRule::Path(path.to_string()),
+            args: Vec::new(),
+        }
+    }
+
+    pub fn name(name: &str) -> Self {
+        MatcherConfig {
+            rule: MatchRule::Name(name.to_string()),
+            args: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum CompiledRule {
+    Name(String),
+    Path(String),
+    Glob(Pattern),
+    Regex(Regex),
+}
+
+#[derive(Debug, Clone)]
+pub struct ProcessMatcher {
+    config: MatcherConfig,
+    rule: CompiledRule,
+    args: Vec<String>,
+}
+
+/// 去掉 `\\?\` 前缀，统一分隔符为 `/` 并转为小写
+pub fn normalize_path(path: &str) -> String {
+    let path = if let Some(rest) = path.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{}", rest)
+    } else {
+        path.strip_prefix(r"\\?\").unwrap_or(path).to_string()
+    };
+    path.replace('\\', "/").to_lowercase()
+}
+
+/// 配置的路径可能是符号链接，尽量解析为真实路径后再规范化
+fn resolve_path(path: &str) -> String {
+    let resolved = fs::canonicalize(path)
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| path.to_string());
+    normalize_path(&resolved)
+}
+
+impl ProcessMatcher {
+    pub fn new(config: MatcherConfig) -> Result<Self, MatcherError> {
+        let rule = match &config.rule {
+            MatchRule::Name(name) => CompiledRule::Name(name.to_lowercase()),
+            MatchRule::Path(path) => CompiledRule::Path(resolve_path(path)),
+            MatchRule::Glob(pattern) => CompiledRule::Glob(Pattern::new(&normalize_path(pattern))?),
+            MatchRule::Regex(pattern) => CompiledRule::Regex(Regex::new(pattern)?),
+        };
+        let args = config.args.iter().map(|a| a.to_lowercase()).collect();
+        Ok(ProcessMatcher { config, rule, args })
+    }
+
+    /// 路径规则不会编译失败
+    pub fn from_path(path: &str) -> Self {
+        ProcessMatcher {
+            config: MatcherConfig::path(path),
+            rule: CompiledRule::Path(resolve_path(path)),
+            args: Vec::new(),
+        }
+    }
+
+    pub fn config(&self) -> &MatcherConfig {
+        &self.config
+    }
+
+    /// 匹配时需要刷新的进程信息
+    pub fn refresh_kind(&self) -> ProcessRefreshKind {
+        let kind = ProcessRefreshKind::nothing().with_exe(UpdateKind::OnlyIfNotSet);
+        if self.args.is_empty() {
+            kind
+        } else {
+            kind.with_cmd(UpdateKind::OnlyIfNotSet)
+        }
+    }
+
+    pub fn matches(&self, name: &str, exe: Option<&Path>, cmd: &[OsString]) -> bool {
+        let rule_matched = match &self.rule {
+            CompiledRule::Name(expected) => {
+                let exe_name = exe
+                    .and_then(|p| p.file_name())
+                    .map(|n| n.to_string_lossy().to_lowercase());
+                name.to_lowercase() == *expected || exe_name.as_deref() == Some(expected)
+            }
+            CompiledRule::Path(expected) => exe
+                .map(|p| normalize_path(&p.to_string_lossy()) == *expected)
+                .unwrap_or(false),
+            CompiledRule::Glob(pattern) => exe
+                .map(|p| {
+                    pattern.matches_with(
+                        &normalize_path(&p.to_string_lossy()),
+                        MatchOptions {
+                            case_sensitive: false,
+                            require_literal_separator: true,
+                            require_literal_leading_dot: false,
+                        },
+                    )
+                })
+                .unwrap_or(false),
+            CompiledRule::Regex(regex) => exe
+                .map(|p| {
+                    let path = p.to_string_lossy();
+                    regex.is_match(path.strip_prefix(r"\\?\").unwrap_or(&path))
+                })
+                .unwrap_or(false),
+        };
+        if !rule_matched {
+            return false;
+        }
+
+        let cmdline = cmd
+            .iter()
+            .map(|a| a.to_string_lossy().to_lowercase())
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.args.iter().all(|arg| cmdline.contains(arg.as_str()))
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MatchedProcess {
+    pub pid: u32,
+    pub name: String,
+    pub exe: Option<String>,
+    pub cmd: Vec<String>,
+}
+
+/// 列出当前所有满足匹配规则的进程
+pub fn find_matching_processes(matcher: &ProcessMatcher) -> Vec<MatchedProcess> {
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        matcher.refresh_kind().with_cmd(UpdateKind::OnlyIfNotSet),
+    );
+    let mut matched = system
+        .processes()
+        .iter()
+        .filter(|(_, p)| matcher.matches(&p.name().to_string_lossy(), p.exe(), p.cmd()))
+        .map(|(pid, p)| MatchedProcess {
+            pid: pid.as_u32(),
+            name: p.name().to_string_lossy().into_owned(),
+            exe: p.exe().map(|e| e.to_string_lossy().into_owned()),
+            cmd: p
+                .cmd()
+                .iter()
+                .map(|a| a.to_string_lossy().into_owned())
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+    matched.sort_by_key(|p| p.pid);
+    matched
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn matcher(rule: MatchRule, args: &[&str]) -> ProcessMatcher {
+        ProcessMatcher::new(MatcherConfig {
+            rule,
+            args: args.iter().map(|a| a.to_string()).collect(),
+        })
+        .unwrap()
+    }
+
+    fn exe(path: &str) -> Option<PathBuf> {
+        Some(PathBuf::from(path))
+    }
+
+    #[test]
+    fn normalize_strips_prefix_separators_and_case() {
+        assert_eq!(
+            normalize_path(r"\\?\E:\Games\VALORANT\live\VALORANT.exe"),
+            "e:/games/valorant/live/valorant.exe"
+        );
+        assert_eq!(
+            normalize_path(r"\\?\UNC\nas\Games\VALORANT.exe"),
+            "//nas/games/valorant.exe"
+        );
+        assert_eq!(normalize_path("E:/Games/A.exe"), "e:/games/a.exe");
+    }
+
+    #[test]
+    fn name_matches_process_or_exe_name_ignoring_case() {
+        let m = matcher(MatchRule::Name("VALORANT.exe".to_string()), &[]);
+        assert!(m.matches("valorant.exe", None, &[]));
+        assert!(m.matches(
+            "VALORANT-Win64-Shipping",
+            exe("/games/Valorant.EXE").as_deref(),
+            &[]
+        ));
+        assert!(!m.matches("other.exe", exe("/games/other.exe").as_deref(), &[]));
+    }
+
+    #[test]
+    fn path_ignores_case_separators_and_verbatim_prefix() {
+        let m = ProcessMatcher::from_path(r"E:\Games\VALORANT\live\VALORANT.exe");
+        assert!(m.matches(
+            "VALORANT.exe",
+            exe(r"\\?\e:\games\valorant\live\valorant.exe").as_deref(),
+            &[]
+        ));
+        assert!(m.matches(
+            "VALORANT.exe",
+            exe("E:/Games/VALORANT/live/VALORANT.exe").as_deref(),
+            &[]
+        ));
+        assert!(!m.matches("VALORANT.exe", None, &[]));
+        assert!(!m.matches(
+            "VALORANT.exe",
+            exe(r"E:\Games\VALORANT\pbe\VALORANT.exe").as_deref(),
+            &[]
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn path_resolves_symlinks_for_default_and_configured_rules() {
+        let dir = crate::utils::test_util::TempDir::new("matcher");
+        fs::create_dir_all(dir.join("real")).unwrap();
+        fs::write(dir.join("real/Game.exe"), "").unwrap();
+        std::os::unix::fs::symlink(dir.join("real"), dir.join("link")).unwrap();
+
+        let linked = dir.join("link/Game.exe").to_string_lossy().into_owned();
+        let real = fs::canonicalize(dir.join("real/Game.exe")).unwrap();
+        let default = ProcessMatcher::from_path(&linked);
+        let configured = matcher(MatchRule::Path(linked), &[]);
+
+        assert!(default.matches("Game.exe", Some(&real), &[]));
+        assert!(configured.matches("Game.exe", Some(&real), &[]));
+    }
+
+    #[test]
+    fn glob_matches_normalized_path() {
+        let m = matcher(MatchRule::Glob(r"**\VALORANT\live\*.exe".to_string()), &[]);
+        assert!(m.matches(
+            "VALORANT.exe",
+            exe(r"\\?\E:\Games\Valorant\Live\VALORANT.exe").as_deref(),
+            &[]
+        ));
+        // `*` 不跨目录
+        assert!(!m.matches(
+            "VALORANT.exe",
+            exe(r"E:\Games\VALORANT\live\bin\VALORANT.exe").as_deref(),
+            &[]
+        ));
+    }
+
+    #[test]
+    fn regex_matches_path_without_verbatim_prefix() {
+        let m = matcher(
+            MatchRule::Regex(r"(?i)^e:\\games\\.*\.exe$".to_string()),
+            &[],
+        );
+        assert!(m.matches(
+            "VALORANT.exe",
+            exe(r"\\?\E:\Games\VALORANT.exe").as_deref(),
+            &[]
+        ));
+        assert!(!m.matches(
+            "VALORANT.exe",
+            exe(r"D:\Games\VALORANT.exe").as_deref(),
+            &[]
+        ));
+        assert!(ProcessMatcher::new(MatcherConfig {
+            rule: MatchRule::Regex("(".to_string()),
+            args: Vec::new(),
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn args_must_all_appear_in_command_line() {
+        let m = matcher(
+            MatchRule::Name("RiotClientServices.exe".to_string()),
+            &["--launch-product=VALORANT", "--launch-patchline"],
+        );
+        let cmd = |args: &[&str]| args.iter().map(OsString::from).collect::<Vec<_>>();
+        assert!(m.matches(
+            "RiotClientServices.exe",
+            None,
+            &cmd(&[
+                "RiotClientServices.exe",
+                "--Launch-Product=valorant",
+                "--launch-patchline=live"
+            ])
+        ));
+        assert!(!m.matches(
+            "RiotClientServices.exe",
+            None,
+            &cmd(&["RiotClientServices.exe", "--launch-product=valorant"])
+        ));
+    }
+}
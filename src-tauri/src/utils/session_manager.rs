@@ -0,0 +1,220 @@
+use std::time::{Duration, Instant};
+
+/// 游戏会话状态：Idle → Pending → Active → Exiting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionState {
+    /// 未检测到游戏
+    Idle,
+    /// 检测到游戏，等待启动延迟
+    Pending { since: Instant },
+    /// 已切换分辨率
+    Active { since: Instant },
+    /// 游戏消失，等待退出宽限期
+    Exiting { since: Instant, active_since: Instant },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionAction {
+    Apply,
+    Restore,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SessionTimings {
+    /// 进程持续存在多久后才切换分辨率
+    pub start_delay: Duration,
+    /// 进程消失多久后才还原分辨率
+    pub exit_grace: Duration,
+    /// 切换后至少保持的时间
+    pub min_session: Duration,
+}
+
+/// 对进程存在状态去抖，避免补丁或反作弊重启游戏时反复切换分辨率
+#[derive(Debug, Clone)]
+pub struct SessionMachine {
+    timings: SessionTimings,
+    state: SessionState,
+}
+
+impl SessionMachine {
+    pub fn new(timings: SessionTimings) -> Self {
+        SessionMachine {
+            timings,
+            state: SessionState::Idle,
+        }
+    }
+
+    pub fn state(&self) -> SessionState {
+        self.state
+    }
+
+    /// 分辨率是否处于已切换状态
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self.state,
+            SessionState::Active { .. } | SessionState::Exiting { .. }
+        )
+    }
+
+    /// 根据本次轮询结果推进状态
+    ///
+    /// `extra_grace` 用于临时延长退出宽限期，例如登录器仍在运行时等待其重新拉起游戏。
+    pub fn update(
+        &mut self,
+        present: bool,
+        extra_grace: Duration,
+        now: Instant,
+    ) -> Option<SessionAction> {
+        match (self.state, present) {
+            (SessionState::Idle, true) => {
+                self.state = SessionState::Pending { since: now };
+                self.try_activate(now)
+            }
+            (SessionState::Idle, false) => None,
+            (SessionState::Pending { .. }, true) => self.try_activate(now),
+            (SessionState::Pending { .. }, false) => {
+                self.state = SessionState::Idle;
+                None
+            }
+            (SessionState::Active { .. }, true) => None,
+            (SessionState::Active { since }, false) => {
+                self.state = SessionState::Exiting {
+                    since: now,
+                    active_since: since,
+                };
+                self.try_restore(extra_grace, now)
+            }
+            (SessionState::Exiting { active_since, .. }, true) => {
+                self.state = SessionState::Active {
+                    since: active_since,
+                };
+                None
+            }
+            (SessionState::Exiting { .. }, false) => self.try_restore(extra_grace, now),
+        }
+    }
+
+    fn try_activate(&mut self, now: Instant) -> Option<SessionAction> {
+        let SessionState::Pending { since } = self.state else {
+            return None;
+        };
+        if now.duration_since(since) >= self.timings.start_delay {
+            self.state = SessionState::Active { since: now };
+            Some(SessionAction::Apply)
+        } else {
+            None
+        }
+    }
+
+    fn try_restore(&mut self, extra_grace: Duration, now: Instant) -> Option<SessionAction> {
+        let SessionState::Exiting {
+            since,
+            active_since,
+        } = self.state
+        else {
+            return None;
+        };
+        let grace = self.timings.exit_grace.max(extra_grace);
+        if now.duration_since(since) >= grace
+            && now.duration_since(active_since) >= self.timings.min_session
+        {
+            self.state = SessionState::Idle;
+            Some(SessionAction::Restore)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(n: u64) -> Duration {
+        Duration::from_secs(n)
+    }
+
+    fn machine(start_delay: u64, exit_grace: u64, min_session: u64) -> SessionMachine {
+        SessionMachine::new(SessionTimings {
+            start_delay: secs(start_delay),
+            exit_grace: secs(exit_grace),
+            min_session: secs(min_session),
+        })
+    }
+
+    #[test]
+    fn zero_timings_switch_immediately() {
+        let t0 = Instant::now();
+        let mut m = machine(0, 0, 0);
+        assert_eq!(m.update(true, Duration::ZERO, t0), Some(SessionAction::Apply));
+        assert!(m.is_active());
+        assert_eq!(m.update(false, Duration::ZERO, t0 + secs(1)), Some(SessionAction::Restore));
+        assert_eq!(m.state(), SessionState::Idle);
+    }
+
+    #[test]
+    fn start_delay_waits_before_apply() {
+        let t0 = Instant::now();
+        let mut m = machine(3, 0, 0);
+        assert_eq!(m.update(true, Duration::ZERO, t0), None);
+        assert_eq!(m.state(), SessionState::Pending { since: t0 });
+        assert_eq!(m.update(true, Duration::ZERO, t0 + secs(2)), None);
+        assert_eq!(m.update(true, Duration::ZERO, t0 + secs(3)), Some(SessionAction::Apply));
+        assert_eq!(m.state(), SessionState::Active { since: t0 + secs(3) });
+    }
+
+    #[test]
+    fn short_blip_during_start_delay_is_ignored() {
+        let t0 = Instant::now();
+        let mut m = machine(3, 0, 0);
+        m.update(true, Duration::ZERO, t0);
+        assert_eq!(m.update(false, Duration::ZERO, t0 + secs(1)), None);
+        assert_eq!(m.state(), SessionState::Idle);
+        // 重新出现时重新计时
+        assert_eq!(m.update(true, Duration::ZERO, t0 + secs(2)), None);
+        assert_eq!(m.update(true, Duration::ZERO, t0 + secs(4)), None);
+        assert_eq!(m.update(true, Duration::ZERO, t0 + secs(5)), Some(SessionAction::Apply));
+    }
+
+    #[test]
+    fn restart_within_exit_grace_keeps_mode() {
+        let t0 = Instant::now();
+        let mut m = machine(0, 5, 0);
+        m.update(true, Duration::ZERO, t0);
+        assert_eq!(m.update(false, Duration::ZERO, t0 + secs(10)), None);
+        assert!(matches!(m.state(), SessionState::Exiting { .. }));
+        assert_eq!(m.update(true, Duration::ZERO, t0 + secs(12)), None);
+        assert_eq!(m.state(), SessionState::Active { since: t0 });
+    }
+
+    #[test]
+    fn exit_grace_expires_then_restores() {
+        let t0 = Instant::now();
+        let mut m = machine(0, 5, 0);
+        m.update(true, Duration::ZERO, t0);
+        m.update(false, Duration::ZERO, t0 + secs(10));
+        assert_eq!(m.update(false, Duration::ZERO, t0 + secs(14)), None);
+        assert_eq!(m.update(false, Duration::ZERO, t0 + secs(15)), Some(SessionAction::Restore));
+        assert_eq!(m.state(), SessionState::Idle);
+    }
+
+    #[test]
+    fn min_session_holds_mode() {
+        let t0 = Instant::now();
+        let mut m = machine(0, 0, 30);
+        m.update(true, Duration::ZERO, t0);
+        assert_eq!(m.update(false, Duration::ZERO, t0 + secs(5)), None);
+        assert_eq!(m.update(false, Duration::ZERO, t0 + secs(29)), None);
+        assert_eq!(m.update(false, Duration::ZERO, t0 + secs(30)), Some(SessionAction::Restore));
+    }
+
+    #[test]
+    fn extra_grace_extends_exit_grace() {
+        let t0 = Instant::now();
+        let mut m = machine(0, 2, 0);
+        m.update(true, Duration::ZERO, t0);
+        m.update(false, secs(15), t0 + secs(1));
+        assert_eq!(m.update(false, secs(15), t0 + secs(10)), None);
+        assert_eq!(m.update(false, secs(15), t0 + secs(16)), Some(SessionAction::Restore));
+    }
+}
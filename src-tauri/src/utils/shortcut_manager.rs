@@ -0,0 +1,236 @@
+use std::{fs, path::Path};
+
+// MS-SHLLINK 中用到的 LinkFlags
+const HAS_LINK_TARGET_ID_LIST: u32 = 0x01;
+const HAS_LINK_INFO: u32 = 0x02;
+const HAS_NAME: u32 = 0x04;
+const HAS_RELATIVE_PATH: u32 = 0x08;
+const HAS_WORKING_DIR: u32 = 0x10;
+const IS_UNICODE: u32 = 0x80;
+
+const HEADER_SIZE: usize = 0x4C;
+const VOLUME_ID_AND_LOCAL_BASE_PATH: u32 = 0x01;
+
+/// 快捷方式中与目标相关的字段
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ShortcutInfo {
+    /// LinkInfo 中的本地路径，只有 ANSI 版本时可能是乱码
+    pub local_base_path: Option<String>,
+    pub local_base_path_unicode: Option<String>,
+    pub relative_path: Option<String>,
+    pub working_dir: Option<String>,
+}
+
+impl ShortcutInfo {
+    /// 目标程序路径，依次使用 Unicode 路径、纯 ASCII 的 ANSI 路径、工作目录加文件名
+    pub fn target(&self) -> Option<String> {
+        if let Some(path) = &self.local_base_path_unicode {
+            return Some(path.clone());
+        }
+        if let Some(path) = self.local_base_path.as_ref().filter(|p| p.is_ascii()) {
+            return Some(path.clone());
+        }
+        let file_name = self.relative_path.as_deref()?.rsplit(['\\', '/']).next()?;
+        let working_dir = self.working_dir.as_deref()?.trim_end_matches(['\\', '/']);
+        Some(format!("{}\\{}", working_dir, file_name))
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn u16_at(&self, offset: usize) -> Option<u16> {
+        let b = self.bytes.get(offset..offset + 2)?;
+        Some(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn u32_at(&self, offset: usize) -> Option<u32> {
+        let b = self.bytes.get(offset..offset + 4)?;
+        Some(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// 以 0 结尾的单字节字符串
+    fn ansi_at(&self, offset: usize) -> Option<String> {
+        let rest = self.bytes.get(offset..)?;
+        let end = rest.iter().position(|b| *b == 0)?;
+        Some(String::from_utf8_lossy(&rest[..end]).into_owned())
+    }
+
+    /// 以 0 结尾的 UTF-16 字符串
+    fn unicode_at(&self, offset: usize) -> Option<String> {
+        let rest = self.bytes.get(offset..)?;
+        let units = rest
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .take_while(|u| *u != 0)
+            .collect::<Vec<_>>();
+        Some(String::from_utf16_lossy(&units))
+    }
+}
+
+/// 解析 `.lnk` 文件，不依赖 Shell COM 接口
+pub fn parse_shortcut(bytes: &[u8]) -> Option<ShortcutInfo> {
+    let reader = Reader { bytes };
+    if reader.u32_at(0)? as usize != HEADER_SIZE {
+        return None;
+    }
+    let flags = reader.u32_at(0x14)?;
+    let mut offset = HEADER_SIZE;
+    if flags & HAS_LINK_TARGET_ID_LIST != 0 {
+        offset += 2 + reader.u16_at(offset)? as usize;
+    }
+
+    let mut info = ShortcutInfo::default();
+    if flags & HAS_LINK_INFO != 0 {
+        let size = reader.u32_at(offset)? as usize;
+        let header_size = reader.u32_at(offset + 4)?;
+        let link_info_flags = reader.u32_at(offset + 8)?;
+        if link_info_flags & VOLUME_ID_AND_LOCAL_BASE_PATH != 0 {
+            let base = reader.u32_at(offset + 16)? as usize;
+            let suffix = reader.u32_at(offset + 24)? as usize;
+            info.local_base_path = Some(format!(
+                "{}{}",
+                reader.ansi_at(offset + base)?,
+                reader.ansi_at(offset + suffix).unwrap_or_default()
+            ));
+            if header_size >= 0x24 {
+                let base = reader.u32_at(offset + 28)? as usize;
+                let suffix = reader.u32_at(offset + 32)? as usize;
+                info.local_base_path_unicode = Some(format!(
+                    "{}{}",
+                    reader.unicode_at(offset + base)?,
+                    reader.unicode_at(offset + suffix).unwrap_or_default()
+                ));
+            }
+        }
+        offset += size;
+    }
+
+    // StringData 按 NAME、RELATIVE_PATH、WORKING_DIR 的顺序排列
+    let unicode = flags & IS_UNICODE != 0;
+    let mut read_string = |present: bool| -> Option<Option<String>> {
+        if !present {
+            return Some(None);
+        }
+        let count = reader.u16_at(offset)? as usize;
+        offset += 2;
+        let value = if unicode {
+            let bytes = reader.bytes.get(offset..offset + count * 2)?;
+            offset += count * 2;
+            let units = bytes
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .collect::<Vec<_>>();
+            String::from_utf16_lossy(&units)
+        } else {
+            let bytes = reader.bytes.get(offset..offset + count)?;
+            offset += count;
+            String::from_utf8_lossy(bytes).into_owned()
+        };
+        Some(Some(value))
+    };
+    read_string(flags & HAS_NAME != 0)?;
+    info.relative_path = read_string(flags & HAS_RELATIVE_PATH != 0)?;
+    info.working_dir = read_string(flags & HAS_WORKING_DIR != 0)?;
+    Some(info)
+}
+
+/// 读取快捷方式的目标程序路径
+pub fn read_shortcut_target(path: &Path) -> Option<String> {
+    let bytes = fs::read(path).ok()?;
+    parse_shortcut(&bytes)?.target()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16z(s: &str) -> Vec<u8> {
+        s.encode_utf16()
+            .chain([0])
+            .flat_map(|u| u.to_le_bytes())
+            .collect()
+    }
+
+    fn counted_utf16(s: &str) -> Vec<u8> {
+        let units = s.encode_utf16().collect::<Vec<_>>();
+        let mut bytes = (units.len() as u16).to_le_bytes().to_vec();
+        bytes.extend(units.iter().flat_map(|u| u.to_le_bytes()));
+        bytes
+    }
+
+    /// 按 MS-SHLLINK 拼出只含 LinkInfo 和 StringData 的快捷方式
+    fn shortcut(ansi: &[u8], unicode: Option<&str>, relative: &str, working_dir: &str) -> Vec<u8> {
+        let flags = HAS_LINK_INFO | HAS_RELATIVE_PATH | HAS_WORKING_DIR | IS_UNICODE;
+        let mut bytes = vec![0u8; HEADER_SIZE];
+        bytes[..4].copy_from_slice(&(HEADER_SIZE as u32).to_le_bytes());
+        bytes[0x14..0x18].copy_from_slice(&flags.to_le_bytes());
+
+        let header_size: u32 = if unicode.is_some() { 0x24 } else { 0x1C };
+        let mut ansi_z = ansi.to_vec();
+        ansi_z.push(0);
+        let base = header_size;
+        let suffix = base + ansi_z.len() as u32;
+        let mut tail = ansi_z.clone();
+        tail.push(0);
+        let mut fields = vec![
+            header_size,
+            VOLUME_ID_AND_LOCAL_BASE_PATH,
+            0,
+            base,
+            0,
+            suffix,
+        ];
+        if let Some(unicode) = unicode {
+            let base_unicode = suffix + 1;
+            fields.push(base_unicode);
+            fields.push(base_unicode + utf16z(unicode).len() as u32 - 2);
+            tail.extend(utf16z(unicode));
+        }
+        let size = header_size + tail.len() as u32;
+        bytes.extend(size.to_le_bytes());
+        bytes.extend(fields.iter().flat_map(|f| f.to_le_bytes()));
+        bytes.extend(tail);
+        bytes.extend(counted_utf16(relative));
+        bytes.extend(counted_utf16(working_dir));
+        bytes
+    }
+
+    #[test]
+    fn prefers_unicode_base_path() {
+        let target = r"E:\Program Files\ACLOS\Launcher\无畏契约登录器.exe";
+        let bytes = shortcut(b"E:\\??.exe", Some(target), r"..\x.exe", r"E:\Other");
+        let info = parse_shortcut(&bytes).unwrap();
+        assert_eq!(info.local_base_path.as_deref(), Some(r"E:\??.exe"));
+        assert_eq!(info.target().as_deref(), Some(target));
+    }
+
+    #[test]
+    fn falls_back_to_working_dir_for_non_ascii_ansi_path() {
+        let bytes = shortcut(
+            &[b'E', b':', b'\\', 0xce, 0xde, b'.', b'e', b'x', b'e'],
+            None,
+            r"..\..\Launcher\无畏契约登录器.exe",
+            r"E:\Program Files\ACLOS\Launcher\",
+        );
+        assert_eq!(
+            parse_shortcut(&bytes).unwrap().target().as_deref(),
+            Some(r"E:\Program Files\ACLOS\Launcher\无畏契约登录器.exe")
+        );
+
+        let ascii = shortcut(br"C:\Riot Games\VALORANT\live\VALORANT.exe", None, "", "");
+        assert_eq!(
+            parse_shortcut(&ascii).unwrap().target().as_deref(),
+            Some(r"C:\Riot Games\VALORANT\live\VALORANT.exe")
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_files() {
+        let bytes = shortcut(b"C:\\a.exe", None, "", "");
+        assert!(parse_shortcut(&bytes[..HEADER_SIZE + 10]).is_none());
+        assert!(parse_shortcut(b"not a shortcut").is_none());
+    }
+}
@@ -0,0 +1,490 @@
+#[cfg(windows)]
+use std::mem::size_of;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::{
+    broadcast::{error::RecvError, Receiver},
+    Mutex,
+};
+use tracing::{info, warn};
+#[cfg(windows)]
+use windows::{
+    core::{w, PCWSTR},
+    Win32::{
+        Foundation::{HWND, LPARAM},
+        UI::{
+            Shell::{SHAppBarMessage, ABM_GETSTATE, ABM_SETSTATE, ABS_AUTOHIDE, APPBARDATA},
+            WindowsAndMessaging::{
+                FindWindowExW, FindWindowW, IsWindowVisible, ShowWindow, SW_HIDE, SW_SHOW,
+            },
+        },
+    },
+};
+
+use crate::{configs::app_config::AppConfig, utils::watcher_manager::WatcherEvent};
+
+#[derive(Error, Debug)]
+pub enum TaskbarError {
+    #[error("Taskbar window not found")]
+    NotFound,
+    #[error("Failed to save taskbar state: {0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid taskbar state file: {0}")]
+    InvalidSnapshot(#[from] serde_json::Error),
+}
+
+/// 游戏会话期间任务栏的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TaskbarMode {
+    /// 不修改任务栏
+    #[default]
+    Unchanged,
+    /// 完全隐藏任务栏
+    Hide,
+    /// 切换为自动隐藏
+    AutoHide,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TaskbarState {
+    pub visible: bool,
+    pub auto_hide: bool,
+}
+
+/// 任务栏操作抽象，便于在没有任务栏的环境下测试状态跟踪逻辑
+pub trait TaskbarBackend: Send + Sync {
+    fn state(&self) -> Result<TaskbarState, TaskbarError>;
+    fn set_visible(&self, visible: bool) -> Result<(), TaskbarError>;
+    fn set_auto_hide(&self, auto_hide: bool) -> Result<(), TaskbarError>;
+}
+
+/// 修改任务栏前记录原始状态，并写入快照文件，异常退出后下次启动时据此还原
+pub struct TaskbarController {
+    backend: Box<dyn TaskbarBackend>,
+    snapshot_path: PathBuf,
+    saved: Option<TaskbarState>,
+    mode: TaskbarMode,
+}
+
+impl TaskbarController {
+    pub fn new(backend: Box<dyn TaskbarBackend>, snapshot_path: PathBuf) -> Self {
+        TaskbarController {
+            backend,
+            snapshot_path,
+            saved: None,
+            mode: TaskbarMode::Unchanged,
+        }
+    }
+
+    /// 当前生效的模式，未修改时为 Unchanged
+    pub fn mode(&self) -> TaskbarMode {
+        self.mode
+    }
+
+    /// 按模式修改任务栏，首次修改前保存原始状态
+    pub fn engage(&mut self, mode: TaskbarMode) -> Result<(), TaskbarError> {
+        if mode == TaskbarMode::Unchanged || mode == self.mode {
+            return Ok(());
+        }
+        match self.saved {
+            // 切换模式前先回到原始状态
+            Some(original) => self.apply_state(original)?,
+            None => {
+                let original = self.backend.state()?;
+                write_snapshot(&self.snapshot_path, &original)?;
+                self.saved = Some(original);
+            }
+        }
+        match mode {
+            TaskbarMode::Hide => self.backend.set_visible(false)?,
+            TaskbarMode::AutoHide => self.backend.set_auto_hide(true)?,
+            TaskbarMode::Unchanged => {}
+        }
+        info!("Taskbar mode changed to {:?}", mode);
+        self.mode = mode;
+        Ok(())
+    }
+
+    /// 还原为修改前的状态
+    pub fn release(&mut self) -> Result<(), TaskbarError> {
+        let Some(original) = self.saved else {
+            return Ok(());
+        };
+        self.apply_state(original)?;
+        self.saved = None;
+        self.mode = TaskbarMode::Unchanged;
+        remove_snapshot(&self.snapshot_path)?;
+        info!("Taskbar restored to {:?}", original);
+        Ok(())
+    }
+
+    /// 上次退出时没有还原任务栏，按快照文件还原，返回是否执行了还原
+    pub fn recover(&mut self) -> Result<bool, TaskbarError> {
+        if !self.snapshot_path.exists() {
+            return Ok(false);
+        }
+        let content = fs::read_to_string(&self.snapshot_path)?;
+        match serde_json::from_str::<TaskbarState>(&content) {
+            Ok(original) => {
+                warn!("Recovering taskbar state from last run: {:?}", original);
+                self.saved = Some(original);
+                self.release()?;
+                Ok(true)
+            }
+            Err(e) => {
+                remove_snapshot(&self.snapshot_path)?;
+                Err(e.into())
+            }
+        }
+    }
+
+    fn apply_state(&self, state: TaskbarState) -> Result<(), TaskbarError> {
+        self.backend.set_auto_hide(state.auto_hide)?;
+        self.backend.set_visible(state.visible)
+    }
+}
+
+fn write_snapshot(path: &Path, state: &TaskbarState) -> Result<(), TaskbarError> {
+    fs::write(path, serde_json::to_string(state)?)?;
+    Ok(())
+}
+
+fn remove_snapshot(path: &Path) -> Result<(), TaskbarError> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskbarAction {
+    Engage,
+    Release,
+}
+
+/// 从监听器事件中跟踪游戏会话，只在会话内修改和还原任务栏
+///
+/// 托盘、快捷键手动切换分辨率也会发布 ModeApplied 和 ModeRestored，
+/// 游戏未运行时忽略它们，避免隐藏任务栏或还原用户手动设置的状态。
+#[derive(Debug, Default)]
+pub struct SessionFollower {
+    game_running: bool,
+    engaged: bool,
+}
+
+impl SessionFollower {
+    pub fn handle(&mut self, event: &WatcherEvent) -> Option<TaskbarAction> {
+        match event {
+            WatcherEvent::GameDetected { .. } => {
+                self.game_running = true;
+                None
+            }
+            WatcherEvent::GameExited { .. } => {
+                self.game_running = false;
+                None
+            }
+            WatcherEvent::ModeApplied { .. } if self.game_running => {
+                self.engaged = true;
+                Some(TaskbarAction::Engage)
+            }
+            // 游戏仍在运行时的还原来自手动操作，会话结束时再还原
+            WatcherEvent::ModeRestored | WatcherEvent::ModeRestoreFailed { .. }
+                if self.game_running =>
+            {
+                None
+            }
+            WatcherEvent::ModeRestored
+            | WatcherEvent::ModeRestoreFailed { .. }
+            | WatcherEvent::ModeSuspended => self.release(),
+            WatcherEvent::WatchingStopped { .. } => {
+                self.game_running = false;
+                self.release()
+            }
+            _ => None,
+        }
+    }
+
+    fn release(&mut self) -> Option<TaskbarAction> {
+        std::mem::take(&mut self.engaged).then_some(TaskbarAction::Release)
+    }
+}
+
+/// 先还原上次异常退出遗留的任务栏状态，再随会话开始和结束修改、还原任务栏
+pub async fn follow_sessions(
+    controller: Arc<Mutex<TaskbarController>>,
+    mut receiver: Receiver<WatcherEvent>,
+) {
+    if let Err(e) = controller.lock().await.recover() {
+        warn!("Failed to recover taskbar state: {}", e);
+    }
+    let mut follower = SessionFollower::default();
+    loop {
+        let result = match receiver.recv().await {
+            Ok(event) => match follower.handle(&event) {
+                Some(TaskbarAction::Engage) => {
+                    let mode = AppConfig::load_app_config()
+                        .map(|c| c.watcher.taskbar)
+                        .unwrap_or_default();
+                    controller.lock().await.engage(mode)
+                }
+                Some(TaskbarAction::Release) => controller.lock().await.release(),
+                None => Ok(()),
+            },
+            Err(RecvError::Lagged(skipped)) => {
+                warn!("Taskbar receiver lagged, {} events skipped", skipped);
+                Ok(())
+            }
+            Err(RecvError::Closed) => break,
+        };
+        if let Err(e) = result {
+            warn!("Failed to update taskbar: {}", e);
+        }
+    }
+}
+
+pub struct Win32TaskbarBackend;
+
+#[cfg(windows)]
+impl Win32TaskbarBackend {
+    /// 主任务栏和副显示器上的任务栏
+    fn windows() -> Result<Vec<HWND>, TaskbarError> {
+        let primary = unsafe { FindWindowW(w!("Shell_TrayWnd"), PCWSTR::null()) }
+            .map_err(|_| TaskbarError::NotFound)?;
+        let mut windows = vec![primary];
+        let mut after = None;
+        while let Ok(hwnd) =
+            unsafe { FindWindowExW(None, after, w!("Shell_SecondaryTrayWnd"), PCWSTR::null()) }
+        {
+            windows.push(hwnd);
+            after = Some(hwnd);
+        }
+        Ok(windows)
+    }
+
+    fn app_bar_data(hwnd: HWND) -> APPBARDATA {
+        APPBARDATA {
+            cbSize: size_of::<APPBARDATA>() as u32,
+            hWnd: hwnd,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(windows)]
+impl TaskbarBackend for Win32TaskbarBackend {
+    fn state(&self) -> Result<TaskbarState, TaskbarError> {
+        let primary = Self::windows()?[0];
+        let mut data = Self::app_bar_data(primary);
+        let flags = unsafe { SHAppBarMessage(ABM_GETSTATE, &mut data) } as u32;
+        Ok(TaskbarState {
+            visible: unsafe { IsWindowVisible(primary) }.as_bool(),
+            auto_hide: flags & ABS_AUTOHIDE != 0,
+        })
+    }
+
+    fn set_visible(&self, visible: bool) -> Result<(), TaskbarError> {
+        let command = if visible { SW_SHOW } else { SW_HIDE };
+        for hwnd in Self::windows()? {
+            // 返回值表示调用前是否可见，不代表成功与否
+            let _ = unsafe { ShowWindow(hwnd, command) };
+        }
+        Ok(())
+    }
+
+    fn set_auto_hide(&self, auto_hide: bool) -> Result<(), TaskbarError> {
+        let primary = Self::windows()?[0];
+        let mut data = Self::app_bar_data(primary);
+        data.lParam = LPARAM(if auto_hide { ABS_AUTOHIDE as isize } else { 0 });
+        unsafe { SHAppBarMessage(ABM_SETSTATE, &mut data) };
+        Ok(())
+    }
+}
+
+// 其他平台没有任务栏可操作
+#[cfg(not(windows))]
+impl TaskbarBackend for Win32TaskbarBackend {
+    fn state(&self) -> Result<TaskbarState, TaskbarError> {
+        Err(TaskbarError::NotFound)
+    }
+
+    fn set_visible(&self, _visible: bool) -> Result<(), TaskbarError> {
+        Err(TaskbarError::NotFound)
+    }
+
+    fn set_auto_hide(&self, _auto_hide: bool) -> Result<(), TaskbarError> {
+        Err(TaskbarError::NotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::utils::display_manager::DisplayMode;
+    use crate::utils::test_util::TempDir;
+
+    #[derive(Clone)]
+    struct FakeBackend {
+        state: Arc<Mutex<TaskbarState>>,
+    }
+
+    impl FakeBackend {
+        fn new(visible: bool, auto_hide: bool) -> Self {
+            FakeBackend {
+                state: Arc::new(Mutex::new(TaskbarState { visible, auto_hide })),
+            }
+        }
+
+        fn current(&self) -> TaskbarState {
+            *self.state.lock().unwrap()
+        }
+    }
+
+    impl TaskbarBackend for FakeBackend {
+        fn state(&self) -> Result<TaskbarState, TaskbarError> {
+            Ok(self.current())
+        }
+
+        fn set_visible(&self, visible: bool) -> Result<(), TaskbarError> {
+            self.state.lock().unwrap().visible = visible;
+            Ok(())
+        }
+
+        fn set_auto_hide(&self, auto_hide: bool) -> Result<(), TaskbarError> {
+            self.state.lock().unwrap().auto_hide = auto_hide;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn hide_then_release_restores_original() {
+        let backend = FakeBackend::new(true, false);
+        let dir = TempDir::new("taskbar_hide");
+        let path = dir.join("taskbar.json");
+        let mut controller = TaskbarController::new(Box::new(backend.clone()), path.clone());
+
+        controller.engage(TaskbarMode::Hide).unwrap();
+        assert!(!backend.current().visible);
+        assert!(path.exists());
+
+        controller.release().unwrap();
+        assert_eq!(
+            backend.current(),
+            TaskbarState {
+                visible: true,
+                auto_hide: false
+            }
+        );
+        assert_eq!(controller.mode(), TaskbarMode::Unchanged);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn engaging_twice_keeps_first_snapshot() {
+        let backend = FakeBackend::new(true, true);
+        let dir = TempDir::new("taskbar_twice");
+        let path = dir.join("taskbar.json");
+        let mut controller = TaskbarController::new(Box::new(backend.clone()), path.clone());
+
+        controller.engage(TaskbarMode::Hide).unwrap();
+        controller.engage(TaskbarMode::AutoHide).unwrap();
+        assert_eq!(controller.mode(), TaskbarMode::AutoHide);
+        assert!(backend.current().visible);
+
+        controller.release().unwrap();
+        assert_eq!(
+            backend.current(),
+            TaskbarState {
+                visible: true,
+                auto_hide: true
+            }
+        );
+    }
+
+    #[test]
+    fn unchanged_mode_does_nothing() {
+        let backend = FakeBackend::new(true, false);
+        let dir = TempDir::new("taskbar_unchanged");
+        let path = dir.join("taskbar.json");
+        let mut controller = TaskbarController::new(Box::new(backend.clone()), path.clone());
+
+        controller.engage(TaskbarMode::Unchanged).unwrap();
+        assert!(!path.exists());
+        controller.release().unwrap();
+        assert!(backend.current().visible);
+    }
+
+    #[test]
+    fn recover_restores_state_left_by_crash() {
+        let backend = FakeBackend::new(true, false);
+        let dir = TempDir::new("taskbar_crash");
+        let path = dir.join("taskbar.json");
+        let mut crashed = TaskbarController::new(Box::new(backend.clone()), path.clone());
+        crashed.engage(TaskbarMode::Hide).unwrap();
+        // 模拟进程崩溃：控制器被丢弃，没有调用 release
+        drop(crashed);
+        assert!(!backend.current().visible);
+
+        let mut controller = TaskbarController::new(Box::new(backend.clone()), path.clone());
+        assert!(controller.recover().unwrap());
+        assert!(backend.current().visible);
+        assert!(!path.exists());
+        assert!(!controller.recover().unwrap());
+    }
+
+    #[test]
+    fn follower_ignores_manual_switches_outside_sessions() {
+        let mut follower = SessionFollower::default();
+        let applied = WatcherEvent::ModeApplied {
+            mode: DisplayMode::default(),
+        };
+        // 没有游戏时手动切换和还原都不影响任务栏
+        assert_eq!(follower.handle(&applied), None);
+        assert_eq!(follower.handle(&WatcherEvent::ModeRestored), None);
+
+        follower.handle(&WatcherEvent::GameDetected {
+            pid: 1,
+            exe: "VALORANT.exe".to_string(),
+        });
+        assert_eq!(follower.handle(&applied), Some(TaskbarAction::Engage));
+        assert_eq!(follower.handle(&WatcherEvent::ModeRestored), None);
+        // 切出游戏时还原，切回后重新修改
+        assert_eq!(
+            follower.handle(&WatcherEvent::ModeSuspended),
+            Some(TaskbarAction::Release)
+        );
+        assert_eq!(follower.handle(&applied), Some(TaskbarAction::Engage));
+
+        follower.handle(&WatcherEvent::GameExited { pid: 1 });
+        assert_eq!(
+            follower.handle(&WatcherEvent::ModeRestored),
+            Some(TaskbarAction::Release)
+        );
+        assert_eq!(follower.handle(&applied), None);
+        assert_eq!(
+            follower.handle(&WatcherEvent::WatchingStopped {
+                process_path: String::new(),
+            }),
+            None
+        );
+    }
+
+    #[test]
+    fn recover_discards_corrupted_snapshot() {
+        let backend = FakeBackend::new(false, false);
+        let dir = TempDir::new("taskbar_corrupted");
+        let path = dir.join("taskbar.json");
+        fs::write(&path, "not json").unwrap();
+        let mut controller = TaskbarController::new(Box::new(backend.clone()), path.clone());
+        assert!(controller.recover().is_err());
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,522 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::time::{interval, Duration, MissedTickBehavior};
+use tracing::{info, warn};
+
+use crate::utils::file_manager::{is_read_only, set_read_only};
+
+/// 修改后如何防止游戏覆盖本工具写入的配置
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProtectionPolicy {
+    /// 只写入一次，之后游戏可以自由修改
+    None,
+    /// 写入后设为只读，游戏无法保存任何设置
+    #[default]
+    ReadOnly,
+    /// 不加锁，文件被游戏改写后只重新写入本工具管理的键
+    ReapplyOnChange,
+}
+
+impl ProtectionPolicy {
+    /// 同一文件上多个策略冲突时，只读优先于重新写入，重新写入优先于不保护
+    fn strength(self) -> u8 {
+        match self {
+            ProtectionPolicy::None => 0,
+            ProtectionPolicy::ReapplyOnChange => 1,
+            ProtectionPolicy::ReadOnly => 2,
+        }
+    }
+}
+
+/// 一个 INI 键值覆盖
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Tweak {
+    /// 所在的节，为空时匹配任意位置，缺失时写入第一个节
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    pub key: String,
+    pub value: String,
+}
+
+impl Tweak {
+    pub fn new(key: &str, value: impl ToString) -> Self {
+        Tweak {
+            section: None,
+            key: key.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    pub fn in_section(section: &str, key: &str, value: impl ToString) -> Self {
+        Tweak {
+            section: Some(section.to_string()),
+            ..Tweak::new(key, value)
+        }
+    }
+}
+
+fn is_header(line: &str) -> bool {
+    line.trim_start().starts_with('[')
+}
+
+/// 从节标题的下一行到下一个节标题之前
+fn section_body<S: AsRef<str>>(lines: &[S], header: usize) -> Range<usize> {
+    let end = lines[header + 1..]
+        .iter()
+        .position(|l| is_header(l.as_ref()))
+        .map(|i| header + 1 + i)
+        .unwrap_or(lines.len());
+    header + 1..end
+}
+
+/// 键值覆盖可以匹配的行范围，指定的节不存在时为空
+fn tweak_range<S: AsRef<str>>(lines: &[S], section: Option<&str>) -> Option<Range<usize>> {
+    match section {
+        None => Some(0..lines.len()),
+        Some(section) => {
+            let header = format!("[{}]", section);
+            lines
+                .iter()
+                .position(|l| l.as_ref().trim() == header)
+                .map(|i| section_body(lines, i))
+        }
+    }
+}
+
+/// 一组一起写入、采用同一保护策略的键值覆盖
+#[derive(Debug, Clone)]
+pub struct TweakSet {
+    pub name: String,
+    pub tweaks: Vec<Tweak>,
+    pub protection: ProtectionPolicy,
+    pub targets: Vec<PathBuf>,
+}
+
+/// 写入同一文件的所有键值覆盖，以及该文件最终生效的保护策略
+#[derive(Debug, Clone)]
+pub struct TargetTweaks {
+    pub path: PathBuf,
+    pub tweaks: Vec<Tweak>,
+    pub protection: ProtectionPolicy,
+}
+
+/// 按目标文件合并多组键值覆盖，保持文件首次出现的顺序
+pub fn merge_by_target(tweak_sets: &[TweakSet]) -> Vec<TargetTweaks> {
+    let mut targets: Vec<TargetTweaks> = Vec::new();
+    for tweak_set in tweak_sets {
+        for path in &tweak_set.targets {
+            match targets.iter_mut().find(|t| &t.path == path) {
+                Some(target) => {
+                    target.tweaks.extend(tweak_set.tweaks.iter().cloned());
+                    if tweak_set.protection.strength() > target.protection.strength() {
+                        target.protection = tweak_set.protection;
+                    }
+                }
+                None => targets.push(TargetTweaks {
+                    path: path.clone(),
+                    tweaks: tweak_set.tweaks.clone(),
+                    protection: tweak_set.protection,
+                }),
+            }
+        }
+    }
+    targets
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ProtectionStatus {
+    pub tweak_set: String,
+    pub policy: ProtectionPolicy,
+    pub path: String,
+    pub exists: bool,
+    /// 文件是否带只读属性
+    pub locked: bool,
+}
+
+/// 单个键与预期值不一致
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct KeyDrift {
+    pub key: String,
+    pub expected: String,
+    /// 文件中的实际值，缺少该键时为空
+    pub actual: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DriftReport {
+    pub tweak_set: String,
+    pub path: String,
+    pub exists: bool,
+    pub drift: Vec<KeyDrift>,
+}
+
+/// 逐键比较 INI 内容与键值覆盖，同一个键出现多次时任一处不一致即视为偏差
+pub fn detect_drift(content: &str, tweaks: &[Tweak]) -> Vec<KeyDrift> {
+    tweaks
+        .iter()
+        .filter_map(|tweak| {
+            let prefix = format!("{}=", tweak.key);
+            let lines = content
+                .trim_start_matches('\u{feff}')
+                .lines()
+                .collect::<Vec<_>>();
+            let range = tweak_range(&lines, tweak.section.as_deref()).unwrap_or_default();
+            let mut values = lines[range]
+                .iter()
+                .filter_map(|line| line.strip_prefix(&prefix))
+                .peekable();
+            let actual = match values.peek() {
+                None => None,
+                Some(_) => Some(values.find(|v| *v != tweak.value)?.to_string()),
+            };
+            Some(KeyDrift {
+                key: tweak.key.clone(),
+                expected: tweak.value.clone(),
+                actual,
+            })
+        })
+        .collect()
+}
+
+/// 检查每个目标文件的偏差，不存在的文件不报告偏差
+pub fn check_drift(tweak_set: &TweakSet) -> Result<Vec<DriftReport>, Box<dyn Error>> {
+    let mut reports = Vec::new();
+    for path in &tweak_set.targets {
+        let exists = path.exists();
+        let drift = if exists {
+            detect_drift(&fs::read_to_string(path)?, &tweak_set.tweaks)
+        } else {
+            Vec::new()
+        };
+        reports.push(DriftReport {
+            tweak_set: tweak_set.name.clone(),
+            path: path.to_string_lossy().into_owned(),
+            exists,
+            drift,
+        });
+    }
+    Ok(reports)
+}
+
+/// 把键值覆盖写入 INI 内容，保留 BOM、换行符和其余内容
+///
+/// 已有的键原地替换；缺失的键追加到所在节的末尾，节不存在时在文件末尾新建。
+pub fn apply_to_content(content: &str, tweaks: &[Tweak]) -> String {
+    let (bom, body) = match content.strip_prefix('\u{feff}') {
+        Some(body) => ("\u{feff}", body),
+        None => ("", content),
+    };
+    let newline = if body.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut lines = body.lines().map(String::from).collect::<Vec<_>>();
+
+    for tweak in tweaks {
+        let prefix = format!("{}=", tweak.key);
+        let line = format!("{}={}", tweak.key, tweak.value);
+        let range = tweak_range(&lines, tweak.section.as_deref());
+        let mut found = false;
+        if let Some(range) = range.clone() {
+            for existing in lines[range].iter_mut().filter(|l| l.starts_with(&prefix)) {
+                existing.clone_from(&line);
+                found = true;
+            }
+        }
+        if found {
+            continue;
+        }
+
+        let target = match (&tweak.section, range) {
+            (Some(_), Some(range)) => range,
+            (Some(section), None) => {
+                if lines.last().is_some_and(|l| !l.trim().is_empty()) {
+                    lines.push(String::new());
+                }
+                lines.push(format!("[{}]", section));
+                lines.len()..lines.len()
+            }
+            (None, _) => match lines.iter().position(|l| is_header(l)) {
+                Some(header) => section_body(&lines, header),
+                None => 0..lines.len(),
+            },
+        };
+        // 插在节末尾的空行之前，保留节之间的分隔
+        let mut at = target.end;
+        while at > target.start && lines[at - 1].trim().is_empty() {
+            at -= 1;
+        }
+        lines.insert(at, line);
+    }
+
+    let mut result = format!("{}{}", bom, lines.join(newline));
+    if body.ends_with('\n') {
+        result.push_str(newline);
+    }
+    result
+}
+
+/// 写入键值覆盖并按策略设置只读属性，返回内容是否有改动
+pub fn apply_to_file(
+    path: &Path,
+    tweaks: &[Tweak],
+    protection: ProtectionPolicy,
+) -> Result<bool, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+    let updated = apply_to_content(&content, tweaks);
+    let changed = updated != content;
+    if changed {
+        set_read_only(path, false)?;
+        fs::write(path, updated)?;
+    }
+    set_read_only(path, protection == ProtectionPolicy::ReadOnly)?;
+    Ok(changed)
+}
+
+/// 先写入每个文件的全部键值，再按合并后的策略对每个文件加锁一次
+pub fn apply_tweak_sets(tweak_sets: &[TweakSet]) -> Result<(), Box<dyn Error>> {
+    for target in merge_by_target(tweak_sets) {
+        info!("Applying tweaks to {:?}", target.path);
+        apply_to_file(&target.path, &target.tweaks, target.protection)?;
+    }
+    Ok(())
+}
+
+/// 只按合并后的策略加锁或解锁，不修改内容
+pub fn enforce_protection(tweak_sets: &[TweakSet]) -> Result<(), Box<dyn Error>> {
+    for target in merge_by_target(tweak_sets)
+        .iter()
+        .filter(|t| t.path.exists())
+    {
+        set_read_only(
+            &target.path,
+            target.protection == ProtectionPolicy::ReadOnly,
+        )?;
+    }
+    Ok(())
+}
+
+pub fn protection_status(tweak_set: &TweakSet) -> Vec<ProtectionStatus> {
+    tweak_set
+        .targets
+        .iter()
+        .map(|path| ProtectionStatus {
+            tweak_set: tweak_set.name.clone(),
+            policy: tweak_set.protection,
+            path: path.to_string_lossy().into_owned(),
+            exists: path.exists(),
+            locked: is_read_only(path).unwrap_or(false),
+        })
+        .collect()
+}
+
+const GUARD_INTERVAL: Duration = Duration::from_secs(3);
+/// 每隔多少次检查重新加载一次配置和目标文件
+const GUARD_RELOAD_TICKS: u32 = 10;
+
+/// 监视用户写入过且合并后策略为 ReapplyOnChange 的文件，被改写后重新写入本工具管理的键
+///
+/// `load_tweak_sets` 只应返回用户写入过的键值覆盖，否则会改动用户从未应用过的设置。
+/// 同一文件上有其他组要求只读时，以只读为准，不再守护。
+pub async fn guard_tweak_sets<F>(load_tweak_sets: F)
+where
+    F: Fn() -> Result<Vec<TweakSet>, Box<dyn Error>> + Send + 'static,
+{
+    let mut ticker = interval(GUARD_INTERVAL);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut targets = Vec::new();
+    let mut seen: HashMap<PathBuf, SystemTime> = HashMap::new();
+    let mut tick = 0u32;
+
+    loop {
+        ticker.tick().await;
+        if tick.is_multiple_of(GUARD_RELOAD_TICKS) {
+            targets = match load_tweak_sets() {
+                Ok(sets) => merge_by_target(&sets)
+                    .into_iter()
+                    .filter(|t| t.protection == ProtectionPolicy::ReapplyOnChange)
+                    .collect(),
+                Err(e) => {
+                    warn!("Failed to load tweak sets: {}", e);
+                    Vec::new()
+                }
+            };
+        }
+        tick = tick.wrapping_add(1);
+
+        for target in &targets {
+            let path = &target.path;
+            let Ok(modified) = fs::metadata(path).and_then(|m| m.modified()) else {
+                continue;
+            };
+            if seen.get(path) == Some(&modified) {
+                continue;
+            }
+            match apply_to_file(path, &target.tweaks, target.protection) {
+                Ok(true) => info!("Re-applied tweaks to {:?}", path),
+                Ok(false) => {}
+                Err(e) => warn!("Failed to re-apply tweaks to {:?}: {}", path, e),
+            }
+            // 记录写入后的修改时间，避免把自己的写入当作游戏的改写
+            if let Ok(modified) = fs::metadata(path).and_then(|m| m.modified()) {
+                seen.insert(path.clone(), modified);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_util::TempDir;
+
+    fn tweaks() -> Vec<Tweak> {
+        vec![
+            Tweak::new("ResolutionSizeX", 1440),
+            Tweak::new("FullscreenMode", 2),
+        ]
+    }
+
+    #[test]
+    fn replaces_existing_keys_only() {
+        let content = "[Section]\nResolutionSizeX=1920\nLastUserConfirmedResolutionSizeX=1920\nFullscreenMode=0\n";
+        assert_eq!(
+            apply_to_content(content, &tweaks()),
+            "[Section]\nResolutionSizeX=1440\nLastUserConfirmedResolutionSizeX=1920\nFullscreenMode=2\n"
+        );
+    }
+
+    #[test]
+    fn inserts_missing_keys_into_first_section() {
+        let content = "[Section]\nResolutionSizeX=1920\n\n[Other]\nKey=Value\n";
+        assert_eq!(
+            apply_to_content(content, &tweaks()),
+            "[Section]\nResolutionSizeX=1440\nFullscreenMode=2\n\n[Other]\nKey=Value\n"
+        );
+    }
+
+    #[test]
+    fn section_tweaks_only_touch_their_section() {
+        let tweaks = vec![
+            Tweak::in_section("ScalabilityGroups", "sg.TextureQuality", 0),
+            Tweak::in_section("ScalabilityGroups", "sg.ShadingQuality", 1),
+        ];
+        let content =
+            "[Section]\nsg.TextureQuality=3\n\n[ScalabilityGroups]\nsg.TextureQuality=3\n";
+        let applied = apply_to_content(content, &tweaks);
+        assert_eq!(
+            applied,
+            "[Section]\nsg.TextureQuality=3\n\n[ScalabilityGroups]\nsg.TextureQuality=0\nsg.ShadingQuality=1\n"
+        );
+        assert!(detect_drift(&applied, &tweaks).is_empty());
+
+        // 节不存在时在末尾新建
+        assert_eq!(
+            apply_to_content("[Section]\nA=1\n", &tweaks),
+            "[Section]\nA=1\n\n[ScalabilityGroups]\nsg.TextureQuality=0\nsg.ShadingQuality=1\n"
+        );
+    }
+
+    #[test]
+    fn keeps_bom_and_crlf() {
+        let content = "\u{feff}[Section]\r\nResolutionSizeX=1920\r\nFullscreenMode=2\r\n";
+        assert_eq!(
+            apply_to_content(content, &tweaks()),
+            "\u{feff}[Section]\r\nResolutionSizeX=1440\r\nFullscreenMode=2\r\n"
+        );
+    }
+
+    #[test]
+    fn applied_content_is_stable() {
+        let content = "[Section]\nResolutionSizeX=1920\n";
+        let once = apply_to_content(content, &tweaks());
+        assert_eq!(apply_to_content(&once, &tweaks()), once);
+    }
+
+    #[test]
+    fn drift_is_reported_per_key() {
+        let content = "[Section]\nResolutionSizeX=1920\nFullscreenMode=2\n";
+        assert_eq!(
+            detect_drift(content, &tweaks()),
+            vec![KeyDrift {
+                key: "ResolutionSizeX".to_string(),
+                expected: "1440".to_string(),
+                actual: Some("1920".to_string()),
+            }]
+        );
+
+        let drift = detect_drift("[Section]\r\nFullscreenMode=2\r\n", &tweaks());
+        assert_eq!(drift.len(), 1);
+        assert_eq!(drift[0].actual, None);
+
+        let applied = apply_to_content(content, &tweaks());
+        assert!(detect_drift(&applied, &tweaks()).is_empty());
+    }
+
+    #[test]
+    fn file_lock_follows_policy() {
+        let dir = TempDir::new("tweak");
+        let path = dir.join("GameUserSettings.ini");
+        fs::write(&path, "[Section]\nResolutionSizeX=1920\n").unwrap();
+
+        assert!(apply_to_file(&path, &tweaks(), ProtectionPolicy::ReadOnly).unwrap());
+        assert!(is_read_only(&path).unwrap());
+
+        // 内容已是目标值时不重写，但仍按新策略解锁
+        assert!(!apply_to_file(&path, &tweaks(), ProtectionPolicy::ReapplyOnChange).unwrap());
+        assert!(!is_read_only(&path).unwrap());
+    }
+
+    #[test]
+    fn shared_target_uses_strongest_policy() {
+        let dir = TempDir::new("tweak_shared");
+        let path = dir.join("GameUserSettings.ini");
+        fs::write(&path, "[Section]\nResolutionSizeX=1920\n").unwrap();
+        let tweak_set = |name: &str, tweaks: Vec<Tweak>, protection| TweakSet {
+            name: name.to_string(),
+            tweaks,
+            protection,
+            targets: vec![path.clone()],
+        };
+        let sets = [
+            tweak_set("Resolution", tweaks(), ProtectionPolicy::ReadOnly),
+            tweak_set(
+                "Graphics",
+                vec![Tweak::new("sg.TextureQuality", 0)],
+                ProtectionPolicy::None,
+            ),
+        ];
+
+        // 后写入的组不能解除前一组要求的只读
+        apply_tweak_sets(&sets).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "[Section]\nResolutionSizeX=1440\nFullscreenMode=2\nsg.TextureQuality=0\n"
+        );
+        assert!(is_read_only(&path).unwrap());
+
+        let merged = merge_by_target(&[
+            tweak_set("Resolution", tweaks(), ProtectionPolicy::ReapplyOnChange),
+            tweak_set("Graphics", Vec::new(), ProtectionPolicy::None),
+        ]);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].protection, ProtectionPolicy::ReapplyOnChange);
+
+        set_read_only(&path, false).unwrap();
+        enforce_protection(&sets[1..]).unwrap();
+        assert!(!is_read_only(&path).unwrap());
+        enforce_protection(&sets).unwrap();
+        assert!(is_read_only(&path).unwrap());
+
+        set_read_only(&path, false).unwrap();
+    }
+}
@@ -1,38 +1,245 @@
 use std::{
-    path::Path,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
+    time::Instant,
 };
 
-use sysinfo::{ProcessesToUpdate, System};
+use serde::Serialize;
+use sysinfo::{Pid, Process, ProcessRefreshKind, ProcessesToUpdate, System};
 use tokio::{
-    sync::Mutex,
-    task::JoinHandle,
-    time::{interval, Duration},
+    sync::{broadcast, Mutex, Notify},
+    task::{block_in_place, JoinHandle},
+    time::{interval, Duration, MissedTickBehavior},
 };
 use tracing::{info, warn};
 
-use crate::utils::display_manager::{DisplayMode, change_display_mode, change_display_mode_for_monitor, restore_default_settings};
+use crate::{
+    configs::app_config::WatcherConfig,
+    utils::{
+        display_manager::{apply_display_mode_verified, restore_default_settings, DisplayMode},
+        layout_manager::{LayoutController, MonitorLayout},
+        matcher_manager::{MatcherError, ProcessMatcher},
+        session_manager::{SessionAction, SessionMachine, SessionState, SessionTimings},
+        window_manager::{
+            is_focused, window_ready, ApplyTrigger, FocusAction, FocusTracker, Win32WindowProbe,
+            WindowProbe,
+        },
+    },
+};
+
+/// 监听器状态变化事件，通过 broadcast 通道发布
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "Type", rename_all_fields = "PascalCase")]
+pub enum WatcherEvent {
+    WatchingStarted { process_path: String },
+    WatchingStopped { process_path: String },
+    /// 触发进程（如登录器）出现，开始检测游戏进程
+    Armed { pid: u32, exe: String },
+    Disarmed,
+    GameDetected { pid: u32, exe: String },
+    GameExited { pid: u32 },
+    ModeApplied { mode: DisplayMode },
+    ModeApplyFailed { mode: DisplayMode, error: String },
+    ModeRestored,
+    ModeRestoreFailed { error: String },
+    /// 手动切换的模式已确认保留
+    ModeConfirmed { mode: DisplayMode },
+    /// 手动切换的模式超时未确认或被取消，已还原
+    ModeReverted { mode: DisplayMode },
+    /// 手动切换的模式还原失败
+    ModeRevertFailed { mode: DisplayMode, error: String },
+    /// 切出游戏超过阈值，临时还原了桌面分辨率
+    ModeSuspended,
+    /// 切换了分辨率方案
+    ProfileSwitched { name: String },
+    /// 启动前重新写入了被游戏改回的设置
+    TweaksReapplied { files: Vec<String> },
+    TweaksReapplyFailed { error: String },
+}
+
+const MIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 在系统进程表中查找目标进程
+///
+/// 优化模式下只刷新进程名/路径；已知 PID 时先只轮询该 PID，
+/// 它退出后再扫描一遍，游戏换了进程（如交给 Shipping 进程）时不会被当成退出。
+fn find_process(
+    system: &mut System,
+    matcher: &ProcessMatcher,
+    known_pid: Option<u32>,
+    optimized: bool,
+) -> Option<(u32, String)> {
+    if !optimized {
+        system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::everything(),
+        );
+    } else {
+        if let Some(pid) = known_pid {
+            let pid = Pid::from_u32(pid);
+            system.refresh_processes_specifics(
+                ProcessesToUpdate::Some(&[pid]),
+                true,
+                ProcessRefreshKind::nothing(),
+            );
+            let found = system
+                .process(pid)
+                .filter(|p| matcher.matches(&p.name().to_string_lossy(), p.exe(), p.cmd()));
+            if let Some(p) = found {
+                return Some((pid.as_u32(), process_label(p)));
+            }
+        }
+        system.refresh_processes_specifics(ProcessesToUpdate::All, true, matcher.refresh_kind());
+    }
+
+    system.processes().iter().find_map(|(pid, p)| {
+        matcher
+            .matches(&p.name().to_string_lossy(), p.exe(), p.cmd())
+            .then(|| (pid.as_u32(), process_label(p)))
+    })
+}
+
+/// 刷新整个进程表，列出所有匹配的进程：PID、路径或进程名、启动时间（Unix 秒）
+pub(crate) fn matching_processes(
+    system: &mut System,
+    matcher: &ProcessMatcher,
+) -> Vec<(u32, String, u64)> {
+    system.refresh_processes_specifics(ProcessesToUpdate::All, true, matcher.refresh_kind());
+    system
+        .processes()
+        .iter()
+        .filter(|(_, p)| matcher.matches(&p.name().to_string_lossy(), p.exe(), p.cmd()))
+        .map(|(pid, p)| (pid.as_u32(), process_label(p), p.start_time()))
+        .collect()
+}
+
+fn matching_pids(system: &System, matcher: &ProcessMatcher) -> Vec<u32> {
+    system
+        .processes()
+        .iter()
+        .filter(|(_, p)| matcher.matches(&p.name().to_string_lossy(), p.exe(), p.cmd()))
+        .map(|(pid, _)| pid.as_u32())
+        .collect()
+}
+
+/// 优先使用完整路径，无法读取时退回进程名
+fn process_label(process: &Process) -> String {
+    process
+        .exe()
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_else(|| process.name().to_string_lossy().into_owned())
+}
 
 pub struct ProcessWatcher {
     pub process_path: String,
     pub display_mode: DisplayMode,
+    pub poll_interval: Duration,
+    pub optimized_polling: bool,
+    pub matcher: ProcessMatcher,
+    /// 触发进程，设置后只有它运行时才检测游戏进程
+    pub trigger: Option<ProcessMatcher>,
+    /// 触发进程退出后保持检测的时间
+    pub trigger_grace: Duration,
+    /// 触发进程仍在运行时，游戏退出后等待其重新拉起游戏的时间
+    pub restart_grace: Duration,
+    /// 启动延迟、退出宽限期和最短会话时长
+    pub timings: SessionTimings,
+    /// 切换分辨率的时机：进程出现、窗口出现或窗口位于前台
+    pub apply_trigger: ApplyTrigger,
+    /// 切出游戏超过该时间后临时还原桌面分辨率
+    pub focus_restore: Option<Duration>,
+    pub window_probe: Arc<dyn WindowProbe>,
+    /// 当前方案的多显示器设置，需要同时设置 layout 才会生效
+    pub monitor_layout: Option<MonitorLayout>,
+    layout: Option<Arc<LayoutController>>,
     is_running: Arc<AtomicBool>,
+    /// 通知监听任务结束，任务会先还原进行中的会话
+    shutdown: Arc<Notify>,
+    events: broadcast::Sender<WatcherEvent>,
     pub(crate) task: Mutex<Option<JoinHandle<()>>>,
 }
 
 impl ProcessWatcher {
-    pub fn new(process_path: String, display_mode: DisplayMode) -> Self {
+    pub fn new(
+        process_path: String,
+        display_mode: DisplayMode,
+        events: broadcast::Sender<WatcherEvent>,
+    ) -> Self {
         Self {
+            matcher: ProcessMatcher::from_path(&process_path),
             process_path,
             display_mode,
+            poll_interval: Duration::from_secs(2),
+            optimized_polling: true,
+            trigger: None,
+            trigger_grace: Duration::from_secs(10),
+            restart_grace: Duration::from_secs(15),
+            timings: SessionTimings::default(),
+            apply_trigger: ApplyTrigger::Process,
+            focus_restore: None,
+            window_probe: Arc::new(Win32WindowProbe),
+            monitor_layout: None,
+            layout: None,
             is_running: Arc::new(AtomicBool::new(false)),
+            shutdown: Arc::new(Notify::new()),
+            events,
             task: Mutex::new(None),
         }
     }
 
+    /// 按配置创建监听器，`monitor_name` 为空时使用默认显示器
+    pub fn from_config(
+        config: &WatcherConfig,
+        monitor_name: String,
+        events: broadcast::Sender<WatcherEvent>,
+        layout: Arc<LayoutController>,
+    ) -> Result<Self, MatcherError> {
+        let mut watcher = ProcessWatcher::new(
+            config.game_path.clone().unwrap_or_default(),
+            DisplayMode {
+                width: config.width,
+                height: config.height,
+                refresh_rate: config.fps,
+                monitor_name,
+                ..Default::default()
+            },
+            events,
+        );
+        watcher.layout = Some(layout);
+        watcher.apply_config(config)?;
+        Ok(watcher)
+    }
+
+    /// 同步配置中的轮询参数和匹配规则，下次 start 时生效
+    pub fn apply_config(&mut self, config: &WatcherConfig) -> Result<(), MatcherError> {
+        self.poll_interval = Duration::from_millis(config.poll_interval_ms);
+        self.optimized_polling = config.optimized_polling;
+        self.matcher = match &config.matcher {
+            Some(matcher_config) => ProcessMatcher::new(matcher_config.clone())?,
+            None => ProcessMatcher::from_path(&self.process_path),
+        };
+        self.trigger = config
+            .trigger
+            .clone()
+            .map(ProcessMatcher::new)
+            .transpose()?;
+        self.trigger_grace = Duration::from_millis(config.trigger_grace_ms);
+        self.restart_grace = Duration::from_millis(config.restart_grace_ms);
+        self.timings = SessionTimings {
+            start_delay: Duration::from_millis(config.start_delay_ms),
+            exit_grace: Duration::from_millis(config.exit_grace_ms),
+            min_session: Duration::from_millis(config.min_session_ms),
+        };
+        self.apply_trigger = config.apply_on;
+        self.focus_restore =
+            (config.focus_restore_ms > 0).then(|| Duration::from_millis(config.focus_restore_ms));
+        self.monitor_layout = config.monitor_layout();
+        Ok(())
+    }
+
     pub fn is_running(&self) -> bool {
         self.is_running.load(Ordering::Relaxed)
     }
@@ -48,50 +255,201 @@ impl ProcessWatcher {
         let process_path = self.process_path.clone();
         let display_mode = self.display_mode.clone();
         let is_running = self.is_running.clone();
+        let shutdown = self.shutdown.clone();
+        let events = self.events.clone();
+        let poll_interval = self.poll_interval.max(MIN_POLL_INTERVAL);
+        let optimized = self.optimized_polling;
+        let matcher = self.matcher.clone();
+        let trigger = self.trigger.clone();
+        let trigger_grace = self.trigger_grace;
+        let restart_grace = self.restart_grace;
+        let timings = self.timings;
+        let apply_trigger = self.apply_trigger;
+        let focus_restore = self.focus_restore;
+        let window_probe = self.window_probe.clone();
+        let monitor_layout = self.monitor_layout.clone();
+        let layout = self.layout.clone();
 
         let task = tokio::spawn(async move {
-            info!("Start watching process: {}", process_path);
+            info!(
+                "Start watching process: {} (matcher: {:?}, trigger: {:?}, interval: {:?}, optimized: {})",
+                process_path,
+                matcher.config(),
+                trigger.as_ref().map(|t| t.config()),
+                poll_interval,
+                optimized
+            );
 
-            let mut ticker = interval(Duration::from_secs(2));
-            let mut system = System::new_all();
+            let mut ticker = interval(poll_interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            let mut system = if optimized {
+                System::new()
+            } else {
+                System::new_all()
+            };
+            let mut game_pid: Option<u32> = None;
+            let mut trigger_pid: Option<u32> = None;
+            let mut armed = trigger.is_none();
+            let mut trigger_lost_at: Option<Instant> = None;
+            let mut session = SessionMachine::new(timings);
+            let mut focus = FocusTracker::new(focus_restore);
 
             let on_start = || {
-                if display_mode.monitor_name.is_empty() {
-                    let _ = change_display_mode(&display_mode, false);
-                } else {
-                    let _ = change_display_mode_for_monitor(&display_mode, false);
+                let mut mode = display_mode.clone();
+                // 先调整多显示器布局，再在目标显示器上切换并校验分辨率
+                if let (Some(layout), Some(monitor_layout)) = (&layout, &monitor_layout) {
+                    match layout.engage(monitor_layout, &mode) {
+                        Ok(target) => mode.monitor_name = target,
+                        Err(e) => warn!("Failed to arrange monitors: {}", e),
+                    }
+                }
+                let event = match apply_display_mode_verified(&mode, false) {
+                    Ok(_) => WatcherEvent::ModeApplied { mode },
+                    Err(e) => WatcherEvent::ModeApplyFailed {
+                        mode,
+                        error: e.to_string(),
+                    },
+                };
+                let _ = events.send(event);
+            };
+            // 布局写入了注册表，要在还原分辨率之前还原
+            let release_layout = || {
+                if let Some(layout) = &layout {
+                    if let Err(e) = layout.release() {
+                        warn!("Failed to restore monitor layout: {}", e);
+                    }
                 }
             };
             let on_stop = || {
-                let _ = restore_default_settings();
+                release_layout();
+                match restore_default_settings() {
+                    Ok(()) => {
+                        let _ = events.send(WatcherEvent::ModeRestored);
+                    }
+                    Err(e) => {
+                        warn!("Failed to restore display mode: {}", e);
+                        let _ = events.send(WatcherEvent::ModeRestoreFailed {
+                            error: e.to_string(),
+                        });
+                    }
+                }
+            };
+            let on_suspend = || {
+                release_layout();
+                match restore_default_settings() {
+                    Ok(()) => {
+                        let _ = events.send(WatcherEvent::ModeSuspended);
+                    }
+                    Err(e) => warn!("Failed to restore display mode while unfocused: {}", e),
+                }
             };
 
             loop {
-                ticker.tick().await;
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    _ = shutdown.notified() => {
+                        // 会话进行中停止监听（如保存配置后重启），按会话结束还原
+                        if session.is_active() {
+                            block_in_place(on_stop);
+                        }
+                        is_running.store(false, Ordering::Relaxed);
+                        break;
+                    }
+                }
+
+                if let Some(trigger) = &trigger {
+                    match find_process(&mut system, trigger, trigger_pid, optimized) {
+                        Some((pid, exe)) => {
+                            trigger_pid = Some(pid);
+                            trigger_lost_at = None;
+                            if !armed {
+                                armed = true;
+                                let _ = events.send(WatcherEvent::Armed { pid, exe });
+                            }
+                        }
+                        None => {
+                            trigger_pid = None;
+                            if armed && session.state() == SessionState::Idle {
+                                let lost_at = *trigger_lost_at.get_or_insert_with(Instant::now);
+                                if lost_at.elapsed() >= trigger_grace {
+                                    armed = false;
+                                    trigger_lost_at = None;
+                                    let _ = events.send(WatcherEvent::Disarmed);
+                                }
+                            }
+                        }
+                    }
+                    if !armed {
+                        continue;
+                    }
+                }
 
-                system.refresh_processes(ProcessesToUpdate::All, true);
+                let found = find_process(&mut system, &matcher, game_pid, optimized);
+                match &found {
+                    Some((pid, exe)) if game_pid != Some(*pid) => {
+                        game_pid = Some(*pid);
+                        let _ = events.send(WatcherEvent::GameDetected {
+                            pid: *pid,
+                            exe: exe.clone(),
+                        });
+                    }
+                    None => {
+                        if let Some(pid) = game_pid.take() {
+                            let _ = events.send(WatcherEvent::GameExited { pid });
+                        }
+                    }
+                    _ => {}
+                }
 
-                let running_now = system.processes().iter().any(|(_, p)| {
-                    p.exe()
-                        .unwrap_or(Path::new("s"))
-                        .to_string_lossy()
-                        .into_owned()
-                        == process_path
-                });
+                // 登录器仍在运行时，给它重新拉起游戏的时间
+                let extra_grace = if trigger_pid.is_some() {
+                    restart_grace
+                } else {
+                    Duration::ZERO
+                };
+                let needs_windows =
+                    apply_trigger != ApplyTrigger::Process || focus_restore.is_some();
+                let pids = if found.is_some() && needs_windows {
+                    matching_pids(&system, &matcher)
+                } else {
+                    Vec::new()
+                };
+                // 窗口触发只决定何时进入会话，会话开始后以进程是否存在为准
+                let present = found.is_some()
+                    && (apply_trigger == ApplyTrigger::Process
+                        || session.is_active()
+                        || window_ready(apply_trigger, window_probe.as_ref(), &pids));
 
-                let running_prev = is_running.swap(running_now, Ordering::Relaxed);
+                let now = Instant::now();
+                let action = session.update(present, extra_grace, now);
+                is_running.store(session.is_active(), Ordering::Relaxed);
+                // 切换分辨率和布局是阻塞的 Win32 调用，期间把工作线程让给其他任务
+                match action {
+                    Some(SessionAction::Apply) => {
+                        focus.reset();
+                        block_in_place(on_start);
+                    }
+                    Some(SessionAction::Restore) => {
+                        focus.reset();
+                        block_in_place(on_stop);
+                    }
+                    None => {}
+                }
 
-                if running_prev != running_now {
-                    if running_now {
-                        (on_start)();
-                    } else {
-                        (on_stop)();
+                if focus_restore.is_some() && matches!(session.state(), SessionState::Active { .. }) {
+                    match focus.update(is_focused(window_probe.as_ref(), &pids), now) {
+                        Some(FocusAction::Suspend) => block_in_place(on_suspend),
+                        Some(FocusAction::Resume) => block_in_place(on_start),
+                        None => {}
                     }
                 }
             }
         });
 
         *task_guard = Some(task);
+        let _ = self.events.send(WatcherEvent::WatchingStarted {
+            process_path: self.process_path.clone(),
+        });
     }
 
     pub async fn stop(&self) {
@@ -99,7 +457,14 @@ impl ProcessWatcher {
 
         if let Some(task) = task_guard.take() {
             info!("{} begins to stop watching", self.process_path);
-            task.abort();
+            self.shutdown.notify_one();
+            // 等监听任务还原进行中的会话后再返回
+            if let Err(e) = task.await {
+                warn!("Watcher task failed: {}", e);
+            }
+            let _ = self.events.send(WatcherEvent::WatchingStopped {
+                process_path: self.process_path.clone(),
+            });
         } else {
             warn!("{} not in watching", self.process_path);
         }
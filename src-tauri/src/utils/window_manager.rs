@@ -0,0 +1,262 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+#[cfg(windows)]
+use windows::{
+    core::BOOL,
+    Win32::{
+        Foundation::{HWND, LPARAM},
+        UI::WindowsAndMessaging::{
+            EnumWindows, GetForegroundWindow, GetWindow, GetWindowThreadProcessId,
+            IsWindowVisible, GW_OWNER,
+        },
+    },
+};
+
+/// 窗口查询抽象，便于在没有真实窗口的环境下测试触发逻辑
+pub trait WindowProbe: Send + Sync {
+    /// 进程是否拥有可见的顶层窗口
+    fn has_top_level_window(&self, pid: u32) -> bool;
+    /// 前台窗口所属的进程
+    fn foreground_pid(&self) -> Option<u32>;
+}
+
+/// 切换分辨率的时机
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ApplyTrigger {
+    /// 检测到进程即切换
+    #[default]
+    Process,
+    /// 进程出现可见的顶层窗口时切换
+    Window,
+    /// 进程窗口位于前台时切换
+    Foreground,
+}
+
+/// 按触发方式判断游戏窗口是否就绪
+pub fn window_ready(trigger: ApplyTrigger, probe: &dyn WindowProbe, pids: &[u32]) -> bool {
+    match trigger {
+        ApplyTrigger::Process => !pids.is_empty(),
+        ApplyTrigger::Window => pids.iter().any(|pid| probe.has_top_level_window(*pid)),
+        ApplyTrigger::Foreground => is_focused(probe, pids),
+    }
+}
+
+pub fn is_focused(probe: &dyn WindowProbe, pids: &[u32]) -> bool {
+    probe
+        .foreground_pid()
+        .is_some_and(|pid| pids.contains(&pid))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusAction {
+    /// 切出游戏超过阈值，临时还原桌面分辨率
+    Suspend,
+    /// 切回游戏，重新应用分辨率
+    Resume,
+}
+
+/// 跟踪游戏窗口是否在前台
+#[derive(Debug, Clone, Default)]
+pub struct FocusTracker {
+    threshold: Option<Duration>,
+    unfocused_since: Option<Instant>,
+    suspended: bool,
+}
+
+impl FocusTracker {
+    /// `threshold` 为 None 时不处理切出
+    pub fn new(threshold: Option<Duration>) -> Self {
+        FocusTracker {
+            threshold,
+            ..Default::default()
+        }
+    }
+
+    pub fn update(&mut self, focused: bool, now: Instant) -> Option<FocusAction> {
+        if focused {
+            self.unfocused_since = None;
+            if self.suspended {
+                self.suspended = false;
+                return Some(FocusAction::Resume);
+            }
+            return None;
+        }
+
+        let threshold = self.threshold?;
+        let since = *self.unfocused_since.get_or_insert(now);
+        if !self.suspended && now.duration_since(since) >= threshold {
+            self.suspended = true;
+            Some(FocusAction::Suspend)
+        } else {
+            None
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.unfocused_since = None;
+        self.suspended = false;
+    }
+}
+
+pub struct Win32WindowProbe;
+
+#[cfg(windows)]
+struct WindowSearch {
+    pid: u32,
+    found: bool,
+}
+
+#[cfg(windows)]
+unsafe extern "system" fn enum_window_callback(hwnd: HWND, lparam: LPARAM) -> BOOL {
+    let search = unsafe { &mut *(lparam.0 as *mut WindowSearch) };
+    let mut window_pid = 0u32;
+    unsafe { GetWindowThreadProcessId(hwnd, Some(&mut window_pid as *mut u32)) };
+    // 只统计可见且没有所有者的顶层窗口
+    let is_top_level = unsafe { GetWindow(hwnd, GW_OWNER) }.is_err();
+    if window_pid == search.pid && is_top_level && unsafe { IsWindowVisible(hwnd) }.as_bool() {
+        search.found = true;
+        return BOOL(0);
+    }
+    BOOL(1)
+}
+
+#[cfg(windows)]
+impl WindowProbe for Win32WindowProbe {
+    fn has_top_level_window(&self, pid: u32) -> bool {
+        let mut search = WindowSearch { pid, found: false };
+        unsafe {
+            // 回调提前返回 FALSE 时 EnumWindows 也会返回错误，这里只关心 found
+            let _ = EnumWindows(
+                Some(enum_window_callback),
+                LPARAM(&mut search as *mut WindowSearch as isize),
+            );
+        }
+        search.found
+    }
+
+    fn foreground_pid(&self) -> Option<u32> {
+        unsafe {
+            let hwnd = GetForegroundWindow();
+            if hwnd.is_invalid() {
+                return None;
+            }
+            let mut pid = 0u32;
+            GetWindowThreadProcessId(hwnd, Some(&mut pid as *mut u32));
+            (pid != 0).then_some(pid)
+        }
+    }
+}
+
+// 其他平台查不到窗口，Window / Foreground 触发方式不会生效
+#[cfg(not(windows))]
+impl WindowProbe for Win32WindowProbe {
+    fn has_top_level_window(&self, _pid: u32) -> bool {
+        false
+    }
+
+    fn foreground_pid(&self) -> Option<u32> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeProbe {
+        windows: Vec<u32>,
+        foreground: Option<u32>,
+    }
+
+    impl WindowProbe for FakeProbe {
+        fn has_top_level_window(&self, pid: u32) -> bool {
+            self.windows.contains(&pid)
+        }
+
+        fn foreground_pid(&self) -> Option<u32> {
+            self.foreground
+        }
+    }
+
+    #[test]
+    fn process_trigger_only_needs_a_pid() {
+        let probe = FakeProbe {
+            windows: vec![],
+            foreground: None,
+        };
+        assert!(window_ready(ApplyTrigger::Process, &probe, &[10]));
+        assert!(!window_ready(ApplyTrigger::Process, &probe, &[]));
+    }
+
+    #[test]
+    fn window_trigger_checks_any_matched_pid() {
+        let probe = FakeProbe {
+            windows: vec![20],
+            foreground: Some(1),
+        };
+        assert!(window_ready(ApplyTrigger::Window, &probe, &[10, 20]));
+        assert!(!window_ready(ApplyTrigger::Window, &probe, &[10]));
+        assert!(!window_ready(ApplyTrigger::Foreground, &probe, &[10, 20]));
+    }
+
+    #[test]
+    fn foreground_trigger_needs_focus() {
+        let probe = FakeProbe {
+            windows: vec![20],
+            foreground: Some(20),
+        };
+        assert!(window_ready(ApplyTrigger::Foreground, &probe, &[10, 20]));
+    }
+
+    #[test]
+    fn focus_loss_suspends_after_threshold_and_resumes() {
+        let t0 = Instant::now();
+        let mut tracker = FocusTracker::new(Some(Duration::from_secs(3)));
+        assert_eq!(tracker.update(true, t0), None);
+        assert_eq!(tracker.update(false, t0 + Duration::from_secs(1)), None);
+        assert_eq!(tracker.update(false, t0 + Duration::from_secs(3)), None);
+        assert_eq!(
+            tracker.update(false, t0 + Duration::from_secs(4)),
+            Some(FocusAction::Suspend)
+        );
+        assert_eq!(tracker.update(false, t0 + Duration::from_secs(9)), None);
+        assert_eq!(
+            tracker.update(true, t0 + Duration::from_secs(10)),
+            Some(FocusAction::Resume)
+        );
+        assert_eq!(tracker.update(true, t0 + Duration::from_secs(11)), None);
+    }
+
+    #[test]
+    fn reset_drops_pending_resume() {
+        let t0 = Instant::now();
+        let mut tracker = FocusTracker::new(Some(Duration::from_secs(3)));
+        tracker.update(false, t0);
+        assert_eq!(
+            tracker.update(false, t0 + Duration::from_secs(3)),
+            Some(FocusAction::Suspend)
+        );
+        // 会话重新开始后不再需要恢复
+        tracker.reset();
+        assert_eq!(tracker.update(true, t0 + Duration::from_secs(4)), None);
+    }
+
+    #[test]
+    fn short_alt_tab_is_ignored() {
+        let t0 = Instant::now();
+        let mut tracker = FocusTracker::new(Some(Duration::from_secs(3)));
+        tracker.update(false, t0);
+        assert_eq!(tracker.update(true, t0 + Duration::from_secs(2)), None);
+        assert_eq!(tracker.update(false, t0 + Duration::from_secs(4)), None);
+        assert_eq!(tracker.update(false, t0 + Duration::from_secs(6)), None);
+    }
+
+    #[test]
+    fn disabled_threshold_never_suspends() {
+        let t0 = Instant::now();
+        let mut tracker = FocusTracker::new(None);
+        assert_eq!(tracker.update(false, t0), None);
+        assert_eq!(tracker.update(false, t0 + Duration::from_secs(3600)), None);
+    }
+}
@@ -0,0 +1,187 @@
+mod common;
+
+use common::{read, FakeInstall, GAME_USER_SETTINGS};
+use fps_enhancer_lib::{
+    games::valorant::{apply_tweaks, preview_tweaks, settings_paths, unlock_targets},
+    utils::{file_manager::is_read_only, tweak_manager::ProtectionPolicy},
+};
+
+/// 当前用户的设置：UTF-8 BOM + CRLF，后面还有其他节
+const USER_SETTINGS: &str = "\u{feff}[/Script/ShooterGame.ShooterGameUserSettings]\r\n\
+bUseVSync=True\r\n\
+ResolutionSizeX=1920\r\n\
+ResolutionSizeY=1080\r\n\
+FullscreenMode=1\r\n\
+\r\n\
+[ShaderPipelineCache.CacheFile]\r\n\
+LastOpened=ShooterGame\r\n";
+
+const USER_SETTINGS_APPLIED: &str = "\u{feff}[/Script/ShooterGame.ShooterGameUserSettings]\r\n\
+bUseVSync=False\r\n\
+ResolutionSizeX=1440\r\n\
+ResolutionSizeY=1080\r\n\
+FullscreenMode=2\r\n\
+bShouldLetterbox=False\r\n\
+bLastConfirmedShouldLetterbox=False\r\n\
+bUseDynamicResolution=False\r\n\
+LastUserConfirmedResolutionSizeX=1440\r\n\
+LastUserConfirmedResolutionSizeY=1080\r\n\
+LastConfirmedFullscreenMode=2\r\n\
+PreferredFullscreenMode=2\r\n\
+\r\n\
+[ShaderPipelineCache.CacheFile]\r\n\
+LastOpened=ShooterGame\r\n";
+
+/// 公共目录的设置：LF，没有 BOM，结尾没有换行
+const PUBLIC_SETTINGS: &str = "[/Script/ShooterGame.ShooterGameUserSettings]\n\
+ResolutionSizeX=1920\n\
+FullscreenMode=0";
+
+const PUBLIC_SETTINGS_APPLIED: &str = "[/Script/ShooterGame.ShooterGameUserSettings]\n\
+ResolutionSizeX=1440\n\
+FullscreenMode=2\n\
+bShouldLetterbox=False\n\
+bLastConfirmedShouldLetterbox=False\n\
+bUseVSync=False\n\
+bUseDynamicResolution=False\n\
+ResolutionSizeY=1080\n\
+LastUserConfirmedResolutionSizeX=1440\n\
+LastUserConfirmedResolutionSizeY=1080\n\
+LastConfirmedFullscreenMode=2\n\
+PreferredFullscreenMode=2";
+
+/// 另一个用户的设置，不应被修改
+const OTHER_SETTINGS: &str =
+    "[/Script/ShooterGame.ShooterGameUserSettings]\r\nResolutionSizeX=2560\r\n";
+
+struct Fixture {
+    install: FakeInstall,
+    user: std::path::PathBuf,
+    public: std::path::PathBuf,
+    other: std::path::PathBuf,
+}
+
+fn fixture(name: &str) -> Fixture {
+    let install = FakeInstall::new(name);
+    install.login("b2c3-uuid");
+    let other = install.write(Some("a1b2-uuid-ap"), GAME_USER_SETTINGS, OTHER_SETTINGS);
+    let user = install.write(Some("b2c3-uuid-ap"), GAME_USER_SETTINGS, USER_SETTINGS);
+    let public = install.write(None, GAME_USER_SETTINGS, PUBLIC_SETTINGS);
+    Fixture {
+        install,
+        user,
+        public,
+        other,
+    }
+}
+
+#[test]
+fn targets_follow_last_known_user() {
+    let f = fixture("targets");
+    let config = f.install.app_config();
+    assert_eq!(
+        settings_paths(&config.valorant).unwrap(),
+        [f.user.clone(), f.public.clone()]
+    );
+}
+
+#[test]
+fn modify_writes_exact_contents_and_locks_targets() {
+    let f = fixture("modify");
+    let config = f.install.app_config();
+    assert_eq!(config.watcher.width, 1440);
+    assert_eq!(config.watcher.height, 1080);
+
+    assert_eq!(apply_tweaks(&config).unwrap(), ["Resolution"]);
+    assert_eq!(read(&f.user), USER_SETTINGS_APPLIED);
+    assert_eq!(read(&f.public), PUBLIC_SETTINGS_APPLIED);
+    assert_eq!(read(&f.other), OTHER_SETTINGS);
+    assert!(is_read_only(&f.user).unwrap());
+    assert!(is_read_only(&f.public).unwrap());
+    assert!(!is_read_only(&f.other).unwrap());
+
+    // 再次写入不会改变内容
+    apply_tweaks(&config).unwrap();
+    assert_eq!(read(&f.user), USER_SETTINGS_APPLIED);
+    assert_eq!(read(&f.public), PUBLIC_SETTINGS_APPLIED);
+}
+
+#[test]
+fn modify_without_protection_leaves_targets_writable() {
+    let f = fixture("unprotected");
+    let mut config = f.install.app_config();
+    config
+        .valorant
+        .protection
+        .insert("Resolution".to_string(), ProtectionPolicy::None);
+
+    apply_tweaks(&config).unwrap();
+    assert_eq!(read(&f.user), USER_SETTINGS_APPLIED);
+    assert!(!is_read_only(&f.user).unwrap());
+    assert!(!is_read_only(&f.public).unwrap());
+}
+
+#[test]
+fn restore_unlocks_without_touching_contents() {
+    let f = fixture("restore");
+    let config = f.install.app_config();
+    apply_tweaks(&config).unwrap();
+
+    unlock_targets(&config).unwrap();
+    assert!(!is_read_only(&f.user).unwrap());
+    assert!(!is_read_only(&f.public).unwrap());
+    assert_eq!(read(&f.user), USER_SETTINGS_APPLIED);
+    assert_eq!(read(&f.public), PUBLIC_SETTINGS_APPLIED);
+}
+
+#[test]
+fn preview_reports_drift_without_writing() {
+    let f = fixture("preview");
+    let config = f.install.app_config();
+
+    let reports = preview_tweaks(&config).unwrap();
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports[0].path, f.user.to_string_lossy());
+    assert!(reports
+        .iter()
+        .all(|r| r.exists && r.tweak_set == "Resolution"));
+    let drift = |index: usize| {
+        reports[index]
+            .drift
+            .iter()
+            .map(|d| (d.key.as_str(), d.actual.as_deref()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        drift(0),
+        [
+            ("bShouldLetterbox", None),
+            ("bLastConfirmedShouldLetterbox", None),
+            ("bUseVSync", Some("True")),
+            ("bUseDynamicResolution", None),
+            ("ResolutionSizeX", Some("1920")),
+            ("LastUserConfirmedResolutionSizeX", None),
+            ("LastUserConfirmedResolutionSizeY", None),
+            ("LastConfirmedFullscreenMode", None),
+            ("PreferredFullscreenMode", None),
+            ("FullscreenMode", Some("1")),
+        ]
+    );
+    assert_eq!(drift(1).len(), 11);
+    assert_eq!(read(&f.user), USER_SETTINGS);
+    assert_eq!(read(&f.public), PUBLIC_SETTINGS);
+
+    apply_tweaks(&config).unwrap();
+    let reports = preview_tweaks(&config).unwrap();
+    assert!(reports.iter().all(|r| r.drift.is_empty()));
+}
+
+#[test]
+fn missing_login_record_is_reported() {
+    let install = FakeInstall::new("no_login");
+    install.write(Some("b2c3-uuid-ap"), GAME_USER_SETTINGS, USER_SETTINGS);
+    let config = install.app_config();
+
+    let error = apply_tweaks(&config).unwrap_err().to_string();
+    assert!(error.contains("RiotLocalMachine.ini"), "{}", error);
+}
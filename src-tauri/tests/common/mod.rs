@@ -0,0 +1,92 @@
+//! 在临时目录中搭建假的游戏安装目录
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use fps_enhancer_lib::{
+    configs::app_config::{AppConfig, ClientVariant},
+    utils::file_manager::set_read_only,
+};
+
+/// 国服的 `Saved/Config` 目录，相对于游戏目录
+pub const CONFIG_DIR: [&str; 3] = ["ShooterGame", "Saved", "Config"];
+pub const PLATFORM_DIR: &str = "WindowsClient";
+pub const GAME_USER_SETTINGS: &str = "GameUserSettings.ini";
+
+/// 假的国服安装目录，离开作用域时删除
+pub struct FakeInstall {
+    pub root: PathBuf,
+}
+
+impl FakeInstall {
+    pub fn new(name: &str) -> Self {
+        let root = env::temp_dir().join(format!("fps_enhancer_it_{}_{}", name, std::process::id()));
+        if root.exists() {
+            unlock_all(&root);
+            fs::remove_dir_all(&root).unwrap();
+        }
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("VALORANT.exe"), "").unwrap();
+        FakeInstall { root }
+    }
+
+    pub fn config_dir(&self) -> PathBuf {
+        CONFIG_DIR.iter().fold(self.root.clone(), |p, c| p.join(c))
+    }
+
+    /// 写入 `Saved/Config` 下的文件，`folder` 为空时写入公共目录
+    pub fn write(&self, folder: Option<&str>, file: &str, content: &str) -> PathBuf {
+        let mut dir = self.config_dir();
+        if let Some(folder) = folder {
+            dir.push(folder);
+        }
+        dir.push(PLATFORM_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(file);
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    /// 记录最后登录的用户
+    pub fn login(&self, user: &str) -> PathBuf {
+        self.write(
+            None,
+            "RiotLocalMachine.ini",
+            &format!("[Riot]\r\nLastKnownUser={}\r\n", user),
+        )
+    }
+
+    /// 内置默认配置，游戏目录指向本目录
+    pub fn app_config(&self) -> AppConfig {
+        let mut config =
+            toml::from_str::<AppConfig>(include_str!("../../configs/config.toml")).unwrap();
+        config.valorant.variant = ClientVariant::Cn;
+        config.valorant.game_path = Some(self.root.to_string_lossy().into_owned());
+        config
+    }
+}
+
+impl Drop for FakeInstall {
+    fn drop(&mut self) {
+        unlock_all(&self.root);
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+/// 只读文件在 Windows 上无法删除，清理前先解锁
+fn unlock_all(dir: &Path) {
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            unlock_all(&path);
+        } else {
+            let _ = set_read_only(&path, false);
+        }
+    }
+}
+
+pub fn read(path: &Path) -> String {
+    fs::read_to_string(path).unwrap()
+}
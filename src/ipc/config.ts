@@ -1,7 +1,7 @@
 // src/ipc/config.ts
 
 import { invoke } from "@tauri-apps/api/core";
-import { AppConfig } from "../types";
+import { AppConfig, ProfileConfig } from "../types";
 
 /**
  * 读取完整配置文件
@@ -27,3 +27,18 @@ export async function saveAllConfig(config: AppConfig): Promise<void> {
 export async function resetConfig(): Promise<void> {
   await invoke("reset_config");
 }
+
+/**
+ * 获取所有分辨率方案
+ */
+export async function getProfiles(): Promise<ProfileConfig[]> {
+  return await invoke<ProfileConfig[]>("get_profiles");
+}
+
+/**
+ * 切换分辨率方案，正在监听时立即生效
+ * @param name 方案名称
+ */
+export async function switchProfile(name: string): Promise<void> {
+  await invoke("switch_profile", { name });
+}
@@ -0,0 +1,50 @@
+// src/ipc/graphics.ts
+
+import { invoke } from '@tauri-apps/api/core';
+import type { GraphicsPreset, GraphicsReadout } from '../types';
+
+/**
+ * 获取内置和自定义画质预设
+ */
+export async function getGraphicsPresets(): Promise<GraphicsPreset[]> {
+  return await invoke<GraphicsPreset[]>('get_graphics_presets');
+}
+
+/**
+ * 读取游戏配置中的当前画质
+ */
+export async function readGraphicsSettings(): Promise<GraphicsReadout> {
+  return await invoke<GraphicsReadout>('read_graphics_settings');
+}
+
+/**
+ * 应用画质预设
+ * @param name 预设名称
+ */
+export async function applyGraphicsPreset(name: string): Promise<void> {
+  await invoke('apply_graphics_preset', { name });
+}
+
+/**
+ * 保存自定义画质预设，同名时覆盖
+ */
+export async function saveGraphicsPreset(preset: GraphicsPreset): Promise<GraphicsPreset[]> {
+  return await invoke<GraphicsPreset[]>('save_graphics_preset', { preset });
+}
+
+/**
+ * 导出画质预设到文件
+ * @param name 预设名称
+ * @param path 导出的 TOML 文件路径
+ */
+export async function exportGraphicsPreset(name: string, path: string): Promise<void> {
+  await invoke('export_graphics_preset', { name, path });
+}
+
+/**
+ * 从文件导入自定义画质预设
+ * @param path TOML 文件路径
+ */
+export async function importGraphicsPreset(path: string): Promise<GraphicsPreset> {
+  return await invoke<GraphicsPreset>('import_graphics_preset', { path });
+}
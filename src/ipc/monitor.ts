@@ -1,7 +1,7 @@
 // src/ipc/monitor.ts
 
 import { invoke } from '@tauri-apps/api/core';
-import type { MonitorsMap } from '../types';
+import type { DisplayMode, MonitorsMap, PendingMode } from '../types';
 
 /**
  * 扫描显示器并持久化到 AppState
@@ -26,4 +26,51 @@ export async function getMonitorsMap(): Promise<MonitorsMap> {
 export async function selectMonitor(monitorName: string): Promise<void> {
   // 使用 camelCase 参数名
   await invoke('select_monitor', { monitorName });
-}
\ No newline at end of file
+}
+
+/**
+ * 试用显示模式，需要在 TimeoutMs 内调用 confirmDisplayMode，否则自动还原
+ */
+export async function tryDisplayMode(mode: DisplayMode, permanent: boolean): Promise<PendingMode> {
+  return await invoke<PendingMode>('try_display_mode', { mode, permanent });
+}
+
+/**
+ * 保留等待确认的显示模式
+ */
+export async function confirmDisplayMode(id: number): Promise<void> {
+  await invoke('confirm_display_mode', { id });
+}
+
+/**
+ * 立即还原等待确认的显示模式
+ */
+export async function cancelDisplayMode(): Promise<void> {
+  await invoke('cancel_display_mode');
+}
+
+/**
+ * 立即把方案的分辨率应用到指定显示器（为空时使用默认显示器），需要在超时前确认
+ * permanent 为 true 时确认后写入注册表
+ */
+export async function applyProfileMode(
+  profile: string,
+  monitorName: string,
+  permanent: boolean,
+): Promise<PendingMode> {
+  return await invoke<PendingMode>('apply_profile_mode', { profile, monitorName, permanent });
+}
+
+/**
+ * 还原指定显示器的分辨率，为空时还原所有显示器
+ */
+export async function restoreMonitor(monitorName: string): Promise<void> {
+  await invoke('restore_monitor', { monitorName });
+}
+
+/**
+ * 每个显示器当前的分辨率和刷新率
+ */
+export async function getCurrentModes(): Promise<DisplayMode[]> {
+  return await invoke<DisplayMode[]>('get_current_modes');
+}
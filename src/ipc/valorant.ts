@@ -1,14 +1,38 @@
 // src/ipc/valorant.ts
 
 import { invoke } from '@tauri-apps/api/core';
+import type {
+  DriftReport,
+  InstallDiagnosis,
+  LaunchError,
+  LaunchOptions,
+  LaunchReport,
+  ProtectionPolicy,
+  ProtectionStatus,
+  VariantDiscovery,
+} from '../types';
 
 /**
- * 扫描无畏契约游戏路径
+ * 扫描无畏契约游戏路径，游戏未运行时使用离线查找的结果
  */
 export async function scanGamePath(): Promise<void> {
   await invoke('scan_game_path');
 }
 
+/**
+ * 离线查找每个客户端版本的登录器和游戏目录，结果按可信度排序
+ */
+export async function discoverInstalls(): Promise<VariantDiscovery[]> {
+  return await invoke<VariantDiscovery[]>('discover_installs');
+}
+
+/**
+ * 逐步检查游戏目录和配置文件，返回检查清单和修复建议
+ */
+export async function checkInstall(): Promise<InstallDiagnosis> {
+  return await invoke<InstallDiagnosis>('check_install');
+}
+
 /**
  * 启动无畏契约游戏
  */
@@ -16,6 +40,35 @@ export async function startGame(): Promise<void> {
   await invoke('start_game');
 }
 
+/**
+ * 启动无畏契约并等待游戏进程出现
+ * @param options 启动参数，可先修改 CFG 文件和开始监听
+ * @returns Promise<LaunchReport> 游戏进程 PID 和启动耗时
+ */
+export async function launchGame(options?: LaunchOptions): Promise<LaunchReport> {
+  return await invoke<LaunchReport>('launch_game', { options });
+}
+
+/**
+ * 将启动错误转为可读文本
+ */
+export function formatLaunchError(error: unknown): string {
+  const launchError = error as LaunchError;
+  if (!launchError?.Kind) {
+    return String(error);
+  }
+  switch (launchError.Kind) {
+    case 'LauncherNotSet':
+      return '未设置登录器路径';
+    case 'LauncherNotFound':
+      return `找不到登录器: ${launchError.Detail}`;
+    case 'Timeout':
+      return `等待 ${Math.round(launchError.Detail / 1000)} 秒后仍未检测到游戏进程`;
+    default:
+      return `${launchError.Kind}: ${launchError.Detail}`;
+  }
+}
+
 /**
  * 解锁无畏契约文件权限
  */
@@ -31,10 +84,11 @@ export async function createPresetWatcher(): Promise<void> {
 }
 
 /**
- * 隐藏系统任务栏
+ * 隐藏或还原系统任务栏
+ * @returns Promise<boolean> 任务栏是否处于隐藏状态
  */
-export async function hideWindowsTask(): Promise<void> {
-  await invoke("hide_windows_taskbar");
+export async function hideWindowsTask(): Promise<boolean> {
+  return await invoke<boolean>("hide_windows_taskbar");
 }
 
 /**
@@ -42,4 +96,29 @@ export async function hideWindowsTask(): Promise<void> {
  */
 export async function modifyCfgFile(): Promise<void> {
   await invoke('modify_cfg_file');
-}
\ No newline at end of file
+}
+/**
+ * 获取每个配置文件的保护策略和锁定状态
+ */
+export async function getProtectionStatus(): Promise<ProtectionStatus[]> {
+  return await invoke<ProtectionStatus[]>('get_protection_status');
+}
+
+/**
+ * 设置一组配置覆盖的保护策略
+ * @param tweakSet 配置覆盖名称，如 Resolution
+ * @param policy 保护策略
+ */
+export async function setProtectionPolicy(
+  tweakSet: string,
+  policy: ProtectionPolicy
+): Promise<ProtectionStatus[]> {
+  return await invoke<ProtectionStatus[]>('set_protection_policy', { tweakSet, policy });
+}
+
+/**
+ * 检查配置文件中被游戏改回的设置
+ */
+export async function checkCfgDrift(): Promise<DriftReport[]> {
+  return await invoke<DriftReport[]>('check_cfg_drift');
+}
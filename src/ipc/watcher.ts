@@ -1,6 +1,7 @@
 // src/ipc/watcher.ts
 
 import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 
 /**
  * 创建通用监听器
@@ -34,4 +35,35 @@ export async function getWatchingStatus(): Promise<boolean> {
 export async function getGamingStatus(): Promise<boolean> {
   const result = await invoke<boolean>('get_gaming_status');
   return result;
-}
\ No newline at end of file
+}
+/**
+ * 监听器事件（后端通过 watcher-event 推送）
+ */
+export type WatcherEvent =
+  | { Type: 'WatchingStarted'; ProcessPath: string }
+  | { Type: 'WatchingStopped'; ProcessPath: string }
+  | { Type: 'Armed'; Pid: number; Exe: string }
+  | { Type: 'Disarmed' }
+  | { Type: 'GameDetected'; Pid: number; Exe: string }
+  | { Type: 'GameExited'; Pid: number }
+  | { Type: 'ModeApplied'; Mode: Record<string, unknown> }
+  | { Type: 'ModeApplyFailed'; Mode: Record<string, unknown>; Error: string }
+  | { Type: 'ModeRestored' }
+  | { Type: 'ModeRestoreFailed'; Error: string }
+  | { Type: 'ModeSuspended' }
+  | { Type: 'ModeConfirmed'; Mode: Record<string, unknown> }
+  | { Type: 'ModeReverted'; Mode: Record<string, unknown> }
+  | { Type: 'ModeRevertFailed'; Mode: Record<string, unknown>; Error: string }
+  | { Type: 'ProfileSwitched'; Name: string }
+  | { Type: 'TweaksReapplied'; Files: string[] }
+  | { Type: 'TweaksReapplyFailed'; Error: string };
+
+/**
+ * 订阅监听器事件
+ * @returns Promise<UnlistenFn> 取消订阅函数
+ */
+export async function onWatcherEvent(
+  handler: (event: WatcherEvent) => void,
+): Promise<UnlistenFn> {
+  return listen<WatcherEvent>('watcher-event', (e) => handler(e.payload));
+}
@@ -27,7 +27,7 @@ import {
 } from '@ant-design/icons';
 import { loadAllConfig, saveAllConfig } from '../ipc/config';
 import { toggleWatching, getWatchingStatus, getGamingStatus } from '../ipc/watcher';
-import { startGame } from '../ipc/valorant';
+import { formatLaunchError, launchGame } from '../ipc/valorant';
 import { openFileDialog } from '../ipc/utils';
 import { scanMonitors, getMonitorsMap, selectMonitor } from '../ipc/monitor';
 import type { AppConfig, MonitorsMap } from '../types';
@@ -232,10 +232,10 @@ const HomePage: React.FC = () => {
 
     setStartLoading(true);
     try {
-      await startGame();
-      message.success('游戏启动成功');
+      const report = await launchGame({ ArmWatcher: true });
+      message.success(`游戏启动成功（PID ${report.Pid}，耗时 ${(report.ElapsedMs / 1000).toFixed(1)} 秒）`);
     } catch (error) {
-      message.error(`启动游戏失败: ${error}`);
+      message.error(`启动游戏失败: ${formatLaunchError(error)}`);
     } finally {
       setStartLoading(false);
     }
@@ -106,8 +106,8 @@ const ValorantPage: React.FC = () => {
   const handleHideTask = async () => {
     setHideTaskLoading(true);
     try {
-      await hideWindowsTask();
-      message.success('TODO：该功能还没做呢');
+      const hidden = await hideWindowsTask();
+      message.success(hidden ? '任务栏已隐藏，再次点击还原' : '任务栏已还原');
     } catch (error) {
       message.error(`隐藏任务栏失败: ${error}`);
     } finally {
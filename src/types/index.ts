@@ -8,6 +8,67 @@ export interface WatcherConfig {
   Width: number;
   Height: number;
   Fps: number;
+  PollIntervalMs?: number;
+  OptimizedPolling?: boolean;
+  TriggerGraceMs?: number;
+  RestartGraceMs?: number;
+  StartDelayMs?: number;
+  ExitGraceMs?: number;
+  MinSessionMs?: number;
+  ApplyOn?: 'Process' | 'Window' | 'Foreground';
+  FocusRestoreMs?: number;
+  AutoStart?: boolean;
+  RestoreLastState?: boolean;
+  LastWatching?: boolean;
+  StartMinimized?: boolean;
+  Profiles?: ProfileConfig[];
+  ActiveProfile?: string;
+  Taskbar?: 'Unchanged' | 'Hide' | 'AutoHide';
+  /** 需要同时设置 Trigger，游戏抢先启动时本次不写入 */
+  ReapplyTweaksOnLaunch?: boolean;
+  ConfirmTimeoutMs?: number;
+}
+
+/**
+ * 单个键与预期值的偏差，Actual 为空表示文件中缺少该键
+ */
+export interface KeyDrift {
+  Key: string;
+  Expected: string;
+  Actual: string | null;
+}
+
+/**
+ * 目标文件的偏差检查结果
+ */
+export interface DriftReport {
+  TweakSet: string;
+  Path: string;
+  Exists: boolean;
+  Drift: KeyDrift[];
+}
+
+/**
+ * 分辨率方案
+ */
+export interface ProfileConfig {
+  Name: string;
+  Width: number;
+  Height: number;
+  Fps: number;
+  /** 游戏期间的多显示器布局，退出游戏后还原 */
+  Monitors?: MonitorLayout;
+}
+
+/**
+ * 多显示器布局：目标显示器、是否临时设为主显示器、其他显示器保持或禁用、是否保持相对位置
+ */
+export interface MonitorLayout {
+  /** 为空时使用切换分辨率的显示器，再为空时使用主显示器 */
+  Monitor?: string;
+  MakePrimary?: boolean;
+  Others?: 'Keep' | 'Disable';
+  KeepPositions?: boolean;
 }
 
 /**
@@ -16,6 +77,85 @@ export interface WatcherConfig {
 export interface ValorantConfig {
   LauncherPath: string;
   GamePath: string;
+  /** 覆盖 Saved/Config 所在的根目录 */
+  ConfigRootPath?: string;
+  Variant?: ClientVariant;
+  Clients?: Partial<Record<ClientVariant, ClientProfile>>;
+  Protection?: Record<string, ProtectionPolicy>;
+  /** 写入过的键值覆盖，后台只守护这些 */
+  AppliedTweakSets?: string[];
+  GraphicsPreset?: string;
+  GraphicsPresets?: GraphicsPreset[];
+}
+
+/**
+ * 客户端版本：国服（腾讯）或国际服（Riot）
+ */
+export type ClientVariant = 'Cn' | 'Global';
+
+/**
+ * 客户端信息，用于覆盖内置默认值
+ */
+export interface ClientProfile {
+  LauncherExe: string;
+  LaunchArgs: string[];
+  ProcessNames: string[];
+  ConfigRoot: 'GameDir' | 'LocalAppData';
+  /** 相对于根目录的 Saved/Config，按路径组件列出 */
+  ConfigDir: string[];
+  PlatformDir: string;
+}
+
+/**
+ * 画质等级
+ */
+export type Quality = 'Low' | 'Medium' | 'High' | 'Epic';
+
+/**
+ * 画质设置
+ */
+export interface GraphicsSettings {
+  TextureQuality: Quality;
+  DetailQuality: Quality;
+  MaterialQuality: Quality;
+  AntiAliasing: 'None' | 'Fxaa' | 'Msaa2x' | 'Msaa4x';
+  /** 1、2、4、8、16 */
+  AnisotropicFiltering: number;
+  Bloom: boolean;
+  Distortion: boolean;
+}
+
+/**
+ * 画质预设，Builtin 为内置预设
+ */
+export interface GraphicsPreset {
+  Name: string;
+  Settings: GraphicsSettings;
+  Builtin?: boolean;
+}
+
+/**
+ * 当前画质，Missing 为文件中缺少、按默认值显示的键
+ */
+export interface GraphicsReadout {
+  Settings: GraphicsSettings;
+  Missing: string[];
+}
+
+/**
+ * 配置文件保护策略：不保护、只读锁定、被改写后重新写入
+ */
+export type ProtectionPolicy = 'None' | 'ReadOnly' | 'ReapplyOnChange';
+
+/**
+ * 目标文件的保护状态
+ */
+export interface ProtectionStatus {
+  TweakSet: string;
+  Policy: ProtectionPolicy;
+  Path: string;
+  Exists: boolean;
+  Locked: boolean;
 }
 
 /**
@@ -32,6 +172,15 @@ export interface AppConfig {
   Watcher: WatcherConfig;
   Valorant: ValorantConfig;
   Development: DevelopmentConfig;
+  Hotkeys?: HotkeyConfig[];
+}
+
+/**
+ * 全局快捷键配置，Keys 形如 Ctrl+Alt+F9
+ */
+export interface HotkeyConfig {
+  Action: 'ToggleStretched' | 'CycleProfile' | 'PauseWatcher';
+  Keys: string;
 }
 
 /**
@@ -43,9 +192,24 @@ export type MonitorsMap = Record<string, string>;
  * 显示模式配置
  */
 export interface DisplayMode {
+  Width: number;
+  Height: number;
+  RefreshRate: number;
+  BitsPerPixel: number;
   MonitorName: string;
 }
 
+/**
+ * 手动切换后等待确认的显示模式，超时未确认时自动还原
+ */
+export interface PendingMode {
+  Id: number;
+  Mode: DisplayMode;
+  /** 确认后是否写入注册表 */
+  Permanent: boolean;
+  TimeoutMs: number;
+}
+
 /**
  * AppState 中的 Watcher 状态
  */
@@ -59,4 +223,86 @@ export interface WatcherState {
 export interface AppStateData {
   Monitors: MonitorsMap;
   Watcher: WatcherState | null;
-}
\ No newline at end of file
+}
+/**
+ * 启动游戏参数
+ */
+export interface LaunchOptions {
+  Args?: string[];
+  ApplyCfg?: boolean;
+  ArmWatcher?: boolean;
+  TimeoutMs?: number;
+}
+
+/**
+ * 启动结果
+ */
+export interface LaunchReport {
+  Pid: number;
+  Exe: string;
+  ElapsedMs: number;
+}
+
+/**
+ * 启动失败原因
+ */
+export type LaunchError =
+  | { Kind: 'ConfigFailed'; Detail: string }
+  | { Kind: 'LauncherNotSet' }
+  | { Kind: 'LauncherNotFound'; Detail: string }
+  | { Kind: 'CfgFailed'; Detail: string }
+  | { Kind: 'WatcherFailed'; Detail: string }
+  | { Kind: 'SpawnFailed'; Detail: string }
+  | { Kind: 'Timeout'; Detail: number };
+
+/**
+ * 安装位置线索的来源
+ */
+export type EvidenceSource = 'InstallRoot' | 'LauncherManifest' | 'Shortcut' | 'UninstallRegistry';
+
+/**
+ * 离线查找到的候选路径
+ */
+export interface InstallCandidate {
+  Path: string;
+  Score: number;
+  Evidence: { Source: EvidenceSource; Detail: string }[];
+}
+
+/**
+ * 离线查找结果
+ */
+export interface DiscoveryReport {
+  Launchers: InstallCandidate[];
+  Games: InstallCandidate[];
+}
+
+/**
+ * 安装检查的步骤和结果
+ */
+export type InstallCheck = 'GameExe' | 'ConfigDir' | 'LocalMachineIni' | 'UserFolder' | 'SettingsFile';
+export type CheckStatus = 'Passed' | 'Warning' | 'Failed' | 'Skipped';
+
+export interface CheckItem {
+  Check: InstallCheck;
+  Status: CheckStatus;
+  Path?: string;
+  Detail: string;
+  /** 未通过时的修复建议 */
+  Hint?: string;
+}
+
+/**
+ * 安装检查清单
+ */
+export interface InstallDiagnosis {
+  Variant: ClientVariant;
+  Items: CheckItem[];
+}
+
+/**
+ * 单个客户端版本的离线查找结果
+ */
+export interface VariantDiscovery extends DiscoveryReport {
+  Variant: ClientVariant;
+}